@@ -0,0 +1,334 @@
+//! Derive macros for the safe ABI layer of `libcmt-binding-rust`.
+//!
+//! Structs map to Solidity tuples, field by field. Enums map to a set of
+//! calls: every variant carries `#[abi(selector = "name(types)")]` and its
+//! fields are the call arguments, so the generated `EncodeCall`/`DecodeCall`
+//! implementations dispatch on the function selector.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    Data, DataEnum, DeriveInput, Error, Fields, GenericParam, Generics, Lifetime, LifetimeParam,
+    LitStr, Variant, parse_macro_input, parse_quote, spanned::Spanned,
+};
+use tiny_keccak::{Hasher, Keccak};
+
+#[proc_macro_derive(AbiEncode, attributes(abi))]
+pub fn derive_abi_encode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_encode(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(AbiDecode, attributes(abi))]
+pub fn derive_abi_decode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_decode(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn abi_path() -> TokenStream2 {
+    quote!(::libcmt_binding_rust::abi)
+}
+
+fn expand_encode(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let abi = abi_path();
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#abi::AbiEncode));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    match &input.data {
+        Data::Struct(data) => {
+            reject_abi_attrs(&input.attrs)?;
+            let tys = data.fields.iter().map(|f| &f.ty);
+            let puts = data.fields.iter().enumerate().map(|(i, f)| {
+                let member = match &f.ident {
+                    Some(ident) => quote!(#ident),
+                    None => {
+                        let index = syn::Index::from(i);
+                        quote!(#index)
+                    }
+                };
+                quote!(enc.put(&self.#member);)
+            });
+            Ok(quote! {
+                impl #impl_generics #abi::AbiEncode for #name #ty_generics #where_clause {
+                    const DYNAMIC: bool = false #(|| <#tys as #abi::AbiEncode>::DYNAMIC)*;
+
+                    fn encode(&self, enc: &mut #abi::AbiEncoder) {
+                        if <Self as #abi::AbiEncode>::DYNAMIC {
+                            let mut sub = #abi::AbiEncoder::new();
+                            #abi::AbiEncode::encode_fields(self, &mut sub);
+                            enc.put_tail(sub.finish());
+                        } else {
+                            #abi::AbiEncode::encode_fields(self, enc);
+                        }
+                    }
+
+                    fn encode_fields(&self, enc: &mut #abi::AbiEncoder) {
+                        #(#puts)*
+                    }
+                }
+            })
+        }
+        Data::Enum(data) => {
+            let arms = data
+                .variants
+                .iter()
+                .zip(enum_funsels(data)?)
+                .map(|(variant, funsel)| {
+                    let ident = &variant.ident;
+                    let bindings: Vec<_> = (0..variant.fields.len())
+                        .map(|i| format_ident!("__field{}", i))
+                        .collect();
+                    let pattern = match &variant.fields {
+                        Fields::Named(fields) => {
+                            let names = fields.named.iter().map(|f| &f.ident);
+                            quote!(Self::#ident { #(#names: #bindings),* })
+                        }
+                        Fields::Unnamed(_) => quote!(Self::#ident(#(#bindings),*)),
+                        Fields::Unit => quote!(Self::#ident),
+                    };
+                    quote! {
+                        #pattern => {
+                            let mut enc = #abi::AbiEncoder::with_funsel(#funsel);
+                            #(enc.put(#bindings);)*
+                            enc.finish()
+                        }
+                    }
+                })
+                .collect::<Vec<_>>();
+            Ok(quote! {
+                impl #impl_generics #abi::EncodeCall for #name #ty_generics #where_clause {
+                    fn encode_call(&self) -> ::std::vec::Vec<u8> {
+                        match self {
+                            #(#arms)*
+                        }
+                    }
+                }
+            })
+        }
+        Data::Union(_) => Err(Error::new_spanned(
+            &input.ident,
+            "AbiEncode cannot be derived for unions",
+        )),
+    }
+}
+
+fn expand_decode(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let abi = abi_path();
+    let name = &input.ident;
+    let (generics, lifetime) = decode_generics(&input.generics);
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    match &input.data {
+        Data::Struct(data) => {
+            reject_abi_attrs(&input.attrs)?;
+            let tys = data.fields.iter().map(|f| &f.ty);
+            let construct = construct(quote!(Self), &data.fields);
+            Ok(quote! {
+                impl #impl_generics #abi::AbiDecode<#lifetime> for #name #ty_generics #where_clause {
+                    const DYNAMIC: bool =
+                        false #(|| <#tys as #abi::AbiDecode<#lifetime>>::DYNAMIC)*;
+
+                    fn decode(
+                        dec: &mut #abi::AbiDecoder<#lifetime>,
+                    ) -> ::std::result::Result<Self, #abi::AbiError> {
+                        if <Self as #abi::AbiDecode<#lifetime>>::DYNAMIC {
+                            <Self as #abi::AbiDecode<#lifetime>>::decode_fields(&mut dec.get_tail()?)
+                        } else {
+                            <Self as #abi::AbiDecode<#lifetime>>::decode_fields(dec)
+                        }
+                    }
+
+                    #[allow(unused_variables)]
+                    fn decode_fields(
+                        dec: &mut #abi::AbiDecoder<#lifetime>,
+                    ) -> ::std::result::Result<Self, #abi::AbiError> {
+                        ::std::result::Result::Ok(#construct)
+                    }
+                }
+            })
+        }
+        Data::Enum(data) => {
+            let branches = decode_enum_branches(data)?;
+            Ok(quote! {
+                impl #impl_generics #abi::DecodeCall<#lifetime> for #name #ty_generics #where_clause {
+                    fn decode_call(
+                        data: &#lifetime [u8],
+                    ) -> ::std::result::Result<Self, #abi::AbiError> {
+                        let found = #abi::AbiDecoder::peek_funsel(data).ok_or(
+                            #abi::AbiError::Truncated {
+                                offset: 0,
+                                needed: 4,
+                                available: data.len(),
+                            },
+                        )?;
+                        #(#branches)*
+                        ::std::result::Result::Err(#abi::AbiError::UnknownFunsel { found })
+                    }
+                }
+            })
+        }
+        Data::Union(_) => Err(Error::new_spanned(
+            &input.ident,
+            "AbiDecode cannot be derived for unions",
+        )),
+    }
+}
+
+fn decode_enum_branches(data: &DataEnum) -> syn::Result<Vec<TokenStream2>> {
+    let abi = abi_path();
+    let funsels = enum_funsels(data)?;
+    Ok(data
+        .variants
+        .iter()
+        .zip(funsels)
+        .map(|(variant, funsel)| {
+            let ident = &variant.ident;
+            let construct = construct(quote!(Self::#ident), &variant.fields);
+            let decoder = if variant.fields.is_empty() {
+                quote!(#abi::AbiDecoder::with_funsel(data, found)?;)
+            } else {
+                quote!(let dec = &mut #abi::AbiDecoder::with_funsel(data, found)?;)
+            };
+            quote! {
+                if found == #funsel {
+                    #decoder
+                    return ::std::result::Result::Ok(#construct);
+                }
+            }
+        })
+        .collect())
+}
+
+/// Builds `path { a: dec.get()?, .. }`, `path(dec.get()?, ..)` or `path`.
+fn construct(path: TokenStream2, fields: &Fields) -> TokenStream2 {
+    match fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|f| &f.ident);
+            quote!(#path { #(#names: dec.get()?),* })
+        }
+        Fields::Unnamed(fields) => {
+            let gets = fields.unnamed.iter().map(|_| quote!(dec.get()?));
+            quote!(#path(#(#gets),*))
+        }
+        Fields::Unit => path,
+    }
+}
+
+/// Returns the generics of the decode impl and the lifetime decoded values
+/// borrow from: the type's first lifetime, or a fresh one if it has none.
+fn decode_generics(generics: &Generics) -> (Generics, Lifetime) {
+    let abi = abi_path();
+    let mut out = generics.clone();
+    let lifetime = match generics.lifetimes().next() {
+        Some(param) => param.lifetime.clone(),
+        None => {
+            let lifetime = Lifetime::new("'__abi", Span::call_site());
+            out.params.insert(
+                0,
+                GenericParam::Lifetime(LifetimeParam::new(lifetime.clone())),
+            );
+            lifetime
+        }
+    };
+    for param in out.type_params_mut() {
+        param.bounds.push(parse_quote!(#abi::AbiDecode<#lifetime>));
+    }
+    (out, lifetime)
+}
+
+/// Returns the selector expressions of the variants of a call enum, in
+/// order. Two variants with the same selector are an error, since only the
+/// first could ever be decoded.
+fn enum_funsels(data: &DataEnum) -> syn::Result<Vec<TokenStream2>> {
+    let mut seen: Vec<(&Variant, [u8; 4])> = Vec::with_capacity(data.variants.len());
+    for variant in &data.variants {
+        let bytes = variant_funsel(variant)?;
+        if let Some((other, _)) = seen.iter().find(|(_, other)| *other == bytes) {
+            return Err(Error::new(
+                variant.span(),
+                format!(
+                    "`{}` has the same selector as `{}`",
+                    variant.ident, other.ident
+                ),
+            ));
+        }
+        seen.push((variant, bytes));
+    }
+    Ok(seen
+        .iter()
+        .map(|(_, bytes)| quote!(u32::from_le_bytes([#(#bytes),*])))
+        .collect())
+}
+
+/// Returns the selector bytes of a call variant. The attribute takes either
+/// a Solidity signature, hashed with Keccak-256 like `keccak::funsel`, or the
+/// four selector bytes as `0x`-prefixed hex. Either way the selector is a
+/// constant in the generated code.
+fn variant_funsel(variant: &Variant) -> syn::Result<[u8; 4]> {
+    let mut selector: Option<LitStr> = None;
+    for attr in variant.attrs.iter().filter(|a| a.path().is_ident("abi")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("selector") {
+                selector = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported abi attribute, expected `selector`"))
+            }
+        })?;
+    }
+    let Some(selector) = selector else {
+        return Err(Error::new(
+            variant.span(),
+            "call variants require #[abi(selector = \"name(types)\")]",
+        ));
+    };
+
+    let value = selector.value();
+    match value.strip_prefix("0x") {
+        Some(digits) => {
+            // Only ASCII hex digits: anything else could put a byte slice
+            // below off a char boundary, and `from_str_radix` would take
+            // a sign.
+            if digits.len() != 8 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(Error::new(
+                    selector.span(),
+                    "selector must be 4 bytes of hex",
+                ));
+            }
+            let mut bytes = [0u8; 4];
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&digits[2 * i..2 * i + 2], 16).unwrap();
+            }
+            Ok(bytes)
+        }
+        None => {
+            // Hash at expansion time, as `keccak::funsel` would at run time,
+            // so decoding does not hash every variant's signature per call.
+            let mut hash = [0u8; 32];
+            let mut keccak = Keccak::v256();
+            keccak.update(value.as_bytes());
+            keccak.finalize(&mut hash);
+            Ok([hash[0], hash[1], hash[2], hash[3]])
+        }
+    }
+}
+
+fn reject_abi_attrs(attrs: &[syn::Attribute]) -> syn::Result<()> {
+    match attrs.iter().find(|a| a.path().is_ident("abi")) {
+        Some(attr) => Err(Error::new_spanned(
+            attr,
+            "#[abi] attributes are only supported on enum variants",
+        )),
+        None => Ok(()),
+    }
+}
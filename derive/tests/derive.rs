@@ -0,0 +1,85 @@
+use libcmt_binding_rust::abi::{self, AbiDecode, AbiEncode, Address, DecodeCall, EncodeCall, U256};
+
+#[derive(Debug, PartialEq, AbiEncode, AbiDecode)]
+struct Point {
+    x: u64,
+    y: bool,
+}
+
+#[derive(Debug, PartialEq, AbiEncode, AbiDecode)]
+struct Named {
+    id: u32,
+    name: String,
+}
+
+#[derive(Debug, PartialEq, AbiEncode, AbiDecode)]
+enum Call {
+    #[abi(selector = "transfer(address,uint256)")]
+    Transfer { to: Address, amount: U256 },
+    #[abi(selector = "0xdeadbeef")]
+    Ping,
+}
+
+fn vector(words: &[&str]) -> Vec<u8> {
+    hex::decode(words.concat()).unwrap()
+}
+
+#[test]
+fn static_struct() {
+    let value = Point { x: 1, y: true };
+    let expected = vector(&[
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "0000000000000000000000000000000000000000000000000000000000000001",
+    ]);
+    const { assert!(!<Point as AbiEncode>::DYNAMIC) };
+    assert_eq!(abi::encode(&value), expected);
+    assert_eq!(abi::decode::<Point>(&expected).unwrap(), value);
+}
+
+#[test]
+fn dynamic_struct() {
+    let value = Named {
+        id: 7,
+        name: "abc".to_string(),
+    };
+    let fields = [
+        "0000000000000000000000000000000000000000000000000000000000000007",
+        "0000000000000000000000000000000000000000000000000000000000000040",
+        "0000000000000000000000000000000000000000000000000000000000000003",
+        "6162630000000000000000000000000000000000000000000000000000000000",
+    ];
+    const { assert!(<Named as AbiEncode>::DYNAMIC) };
+
+    // As the parameter list of a call: the fields in place.
+    let params = vector(&fields);
+    assert_eq!(abi::encode_params(&value), params);
+    assert_eq!(abi::decode_params::<Named>(&params).unwrap(), value);
+
+    // As a single value: an offset to the tuple.
+    let mut words = vec!["0000000000000000000000000000000000000000000000000000000000000020"];
+    words.extend(fields);
+    let single = vector(&words);
+    assert_eq!(abi::encode(&value), single);
+    assert_eq!(abi::decode::<Named>(&single).unwrap(), value);
+}
+
+#[test]
+fn call_enum() {
+    let transfer = Call::Transfer {
+        to: Address::new([0x11; 20]),
+        amount: U256::from(5u8),
+    };
+    let expected = vector(&[
+        "a9059cbb",
+        "0000000000000000000000001111111111111111111111111111111111111111",
+        "0000000000000000000000000000000000000000000000000000000000000005",
+    ]);
+    assert_eq!(transfer.encode_call(), expected);
+    assert_eq!(Call::decode_call(&expected).unwrap(), transfer);
+
+    let ping = vector(&["deadbeef"]);
+    assert_eq!(Call::Ping.encode_call(), ping);
+    assert_eq!(Call::decode_call(&ping).unwrap(), Call::Ping);
+
+    assert!(Call::decode_call(&vector(&["01020304"])).is_err());
+}
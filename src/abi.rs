@@ -3,14 +3,13 @@
 use std::{ffi::c_void, io};
 
 use crate::generated::{
-    CMT_ABI_U256_LENGTH, cmt_abi_check_funsel, cmt_abi_decode_uint, cmt_abi_decode_uint_nn,
-    cmt_abi_decode_uint_nr, cmt_abi_encode_uint, cmt_abi_encode_uint_nn, cmt_abi_encode_uint_nr,
-    cmt_abi_funsel, cmt_abi_get_address, cmt_abi_get_bool, cmt_abi_get_bytes_d,
-    cmt_abi_get_bytes_s, cmt_abi_get_uint, cmt_abi_get_uint_be, cmt_abi_get_uint256,
-    cmt_abi_mark_frame, cmt_abi_peek_bytes_d, cmt_abi_peek_funsel, cmt_abi_put_address,
-    cmt_abi_put_bool, cmt_abi_put_bytes_d, cmt_abi_put_bytes_s, cmt_abi_put_funsel,
-    cmt_abi_put_uint, cmt_abi_put_uint_be, cmt_abi_put_uint256, cmt_abi_reserve_bytes_d,
-    cmt_abi_start_frame,
+    CMT_ABI_U256_LENGTH, cmt_abi_check_funsel, cmt_abi_decode_uint_nn, cmt_abi_decode_uint_nr,
+    cmt_abi_encode_uint_nn, cmt_abi_encode_uint_nr, cmt_abi_funsel, cmt_abi_get_address,
+    cmt_abi_get_bool, cmt_abi_get_bytes_d, cmt_abi_get_bytes_s, cmt_abi_get_uint,
+    cmt_abi_get_uint_be, cmt_abi_get_uint256, cmt_abi_mark_frame, cmt_abi_peek_bytes_d,
+    cmt_abi_peek_funsel, cmt_abi_put_address, cmt_abi_put_bool, cmt_abi_put_bytes_d,
+    cmt_abi_put_bytes_s, cmt_abi_put_funsel, cmt_abi_put_uint, cmt_abi_put_uint_be,
+    cmt_abi_put_uint256, cmt_abi_reserve_bytes_d, cmt_abi_start_frame,
 };
 use crate::{cmt_abi_address_t, cmt_abi_bytes_t, cmt_abi_u256_t, cmt_buf_t, to_io_result};
 
@@ -126,26 +125,51 @@ pub fn peek_bytes_d(
     to_io_result(unsafe { cmt_abi_peek_bytes_d(start, of, bytes) })
 }
 
-pub fn encode_uint(n: usize, data: *const c_void, out: &mut [u8; ABI_U256_LEN]) -> io::Result<()> {
-    to_io_result(unsafe { cmt_abi_encode_uint(n, data, out.as_mut_ptr()) })
+/// Encodes `data`, `data.len() <= 32` bytes in little-endian order, as a
+/// big-endian word; the bytes are reversed ("nr") on the way out.
+pub fn encode_uint_nr(data: &[u8], out: &mut [u8; ABI_U256_LEN]) -> io::Result<()> {
+    to_io_result(unsafe { cmt_abi_encode_uint_nr(data.len(), data.as_ptr(), out.as_mut_ptr()) })
 }
 
-pub fn encode_uint_nr(n: usize, data: *const u8, out: &mut [u8; ABI_U256_LEN]) -> io::Result<()> {
-    to_io_result(unsafe { cmt_abi_encode_uint_nr(n, data, out.as_mut_ptr()) })
+/// Encodes `data`, `data.len() <= 32` bytes already in big-endian order, as a
+/// word; the bytes are copied as is ("nn").
+pub fn encode_uint_nn(data: &[u8], out: &mut [u8; ABI_U256_LEN]) -> io::Result<()> {
+    to_io_result(unsafe { cmt_abi_encode_uint_nn(data.len(), data.as_ptr(), out.as_mut_ptr()) })
 }
 
-pub fn encode_uint_nn(n: usize, data: *const u8, out: &mut [u8; ABI_U256_LEN]) -> io::Result<()> {
-    to_io_result(unsafe { cmt_abi_encode_uint_nn(n, data, out.as_mut_ptr()) })
+/// Decodes a word into `out` in little-endian order, failing if the value
+/// does not fit in `out.len()` bytes.
+pub fn decode_uint_nr(data: &[u8; ABI_U256_LEN], out: &mut [u8]) -> io::Result<()> {
+    to_io_result(unsafe { cmt_abi_decode_uint_nr(data.as_ptr(), out.len(), out.as_mut_ptr()) })
 }
 
-pub fn decode_uint(data: &[u8; ABI_U256_LEN], n: usize, out: *mut u8) -> io::Result<()> {
-    to_io_result(unsafe { cmt_abi_decode_uint(data.as_ptr(), n, out) })
+/// Decodes a word into `out` in big-endian order, failing if the value does
+/// not fit in `out.len()` bytes.
+pub fn decode_uint_nn(data: &[u8; ABI_U256_LEN], out: &mut [u8]) -> io::Result<()> {
+    to_io_result(unsafe { cmt_abi_decode_uint_nn(data.as_ptr(), out.len(), out.as_mut_ptr()) })
 }
 
-pub fn decode_uint_nr(data: &[u8; ABI_U256_LEN], n: usize, out: *mut u8) -> io::Result<()> {
-    to_io_result(unsafe { cmt_abi_decode_uint_nr(data.as_ptr(), n, out) })
-}
+mod codec;
+mod decoder;
+mod encoder;
+mod error;
+mod int;
+mod types;
+mod uint;
 
-pub fn decode_uint_nn(data: &[u8; ABI_U256_LEN], n: usize, out: *mut u8) -> io::Result<()> {
-    to_io_result(unsafe { cmt_abi_decode_uint_nn(data.as_ptr(), n, out) })
-}
+pub mod json;
+pub mod packed;
+
+pub use codec::{
+    AbiDecode, AbiEncode, Bytes, DecodeCall, EncodeCall, decode, decode_call, decode_params,
+    encode, encode_call, encode_params,
+};
+pub use decoder::AbiDecoder;
+pub use encoder::AbiEncoder;
+pub use error::AbiError;
+pub use int::{IntLike, decode_int, encode_int};
+pub use types::{Address, B256, FixedBytes, I256, U256};
+pub use uint::{UintLike, decode_uint, encode_uint};
+
+#[cfg(feature = "derive")]
+pub use libcmt_binding_rust_derive::{AbiDecode, AbiEncode};
@@ -0,0 +1,474 @@
+//! Type driven ABI encoding and decoding for scalars, arrays and tuples.
+//!
+//! Rust types map to Solidity types as follows: `U256`/`u8`..`u128` to `uint`,
+//! `I256`/`i8`..`i128` to `int`, `bool`, `Address`, `FixedBytes<N>` to `bytesN`,
+//! `[u8]`/`&[u8]`/`Bytes` to `bytes`, `str`/`String` to `string`, `Vec<T>` to
+//! `T[]`, `[T; K]` to `T[K]` and Rust tuples to Solidity tuples. Note that
+//! `Vec<u8>` is `uint8[]`; use [`Bytes`] for a dynamic `bytes` value.
+
+use super::decoder::AbiDecoder;
+use super::encoder::AbiEncoder;
+use super::error::AbiError;
+use super::types::{Address, FixedBytes, I256, U256};
+
+/// A Rust type with a Solidity ABI encoding.
+pub trait AbiEncode {
+    /// Whether values of this type live in the tail, behind an offset word.
+    const DYNAMIC: bool;
+
+    /// Appends `self` to `enc` as a single ABI value.
+    fn encode(&self, enc: &mut AbiEncoder);
+
+    /// Appends the members of a tuple as a parameter list. Non-tuple types
+    /// are a single parameter.
+    fn encode_fields(&self, enc: &mut AbiEncoder) {
+        self.encode(enc)
+    }
+}
+
+/// A Rust type that can be decoded from Solidity ABI data borrowed for `'a`.
+pub trait AbiDecode<'a>: Sized {
+    /// Whether values of this type live in the tail, behind an offset word.
+    const DYNAMIC: bool;
+
+    /// Reads a single ABI value from `dec`.
+    fn decode(dec: &mut AbiDecoder<'a>) -> Result<Self, AbiError>;
+
+    /// Reads the members of a tuple from a parameter list. Non-tuple types
+    /// are a single parameter.
+    fn decode_fields(dec: &mut AbiDecoder<'a>) -> Result<Self, AbiError> {
+        Self::decode(dec)
+    }
+}
+
+/// A set of calls told apart by their function selector, e.g. an enum
+/// deriving `AbiEncode` with `#[abi(selector = "...")]` variants.
+pub trait EncodeCall {
+    /// Returns the selector followed by the ABI encoded arguments.
+    fn encode_call(&self) -> Vec<u8>;
+}
+
+/// The decoding half of [`EncodeCall`].
+pub trait DecodeCall<'a>: Sized {
+    /// Dispatches on the selector at the start of `data` and decodes the
+    /// arguments of the matching call.
+    fn decode_call(data: &'a [u8]) -> Result<Self, AbiError>;
+}
+
+/// An owned Solidity `bytes` value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Bytes(pub Vec<u8>);
+
+impl From<Vec<u8>> for Bytes {
+    fn from(data: Vec<u8>) -> Self {
+        Self(data)
+    }
+}
+
+impl From<&[u8]> for Bytes {
+    fn from(data: &[u8]) -> Self {
+        Self(data.to_vec())
+    }
+}
+
+impl AsRef<[u8]> for Bytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Encodes `value` as Solidity's `abi.encode(value)` would.
+pub fn encode<T: AbiEncode + ?Sized>(value: &T) -> Vec<u8> {
+    let mut enc = AbiEncoder::new();
+    enc.put(value);
+    enc.finish()
+}
+
+/// Encodes the members of a tuple as a parameter list, e.g. the arguments of
+/// a call after its selector.
+pub fn encode_params<T: AbiEncode + ?Sized>(value: &T) -> Vec<u8> {
+    let mut enc = AbiEncoder::new();
+    enc.put_params(value);
+    enc.finish()
+}
+
+/// Encodes a call: the selector followed by the tuple `args` as parameters.
+pub fn encode_call<T: AbiEncode + ?Sized>(funsel: u32, args: &T) -> Vec<u8> {
+    let mut enc = AbiEncoder::with_funsel(funsel);
+    enc.put_params(args);
+    enc.finish()
+}
+
+/// Decodes a single value, the inverse of [`encode`].
+pub fn decode<'a, T: AbiDecode<'a>>(data: &'a [u8]) -> Result<T, AbiError> {
+    AbiDecoder::new(data).get()
+}
+
+/// Decodes a parameter list into a tuple, the inverse of [`encode_params`].
+pub fn decode_params<'a, T: AbiDecode<'a>>(data: &'a [u8]) -> Result<T, AbiError> {
+    AbiDecoder::new(data).get_params()
+}
+
+/// Checks the selector of a call and decodes its arguments.
+pub fn decode_call<'a, T: AbiDecode<'a>>(data: &'a [u8], funsel: u32) -> Result<T, AbiError> {
+    AbiDecoder::with_funsel(data, funsel)?.get_params()
+}
+
+impl AbiEncoder {
+    pub fn put<T: AbiEncode + ?Sized>(&mut self, value: &T) -> &mut Self {
+        value.encode(self);
+        self
+    }
+
+    pub fn put_params<T: AbiEncode + ?Sized>(&mut self, value: &T) -> &mut Self {
+        value.encode_fields(self);
+        self
+    }
+}
+
+impl<'a> AbiDecoder<'a> {
+    pub fn get<T: AbiDecode<'a>>(&mut self) -> Result<T, AbiError> {
+        T::decode(self)
+    }
+
+    pub fn get_params<T: AbiDecode<'a>>(&mut self) -> Result<T, AbiError> {
+        T::decode_fields(self)
+    }
+}
+
+macro_rules! impl_static {
+    ($t:ty, $put:ident, $get:ident) => {
+        impl AbiEncode for $t {
+            const DYNAMIC: bool = false;
+
+            fn encode(&self, enc: &mut AbiEncoder) {
+                enc.$put(self);
+            }
+        }
+
+        impl<'a> AbiDecode<'a> for $t {
+            const DYNAMIC: bool = false;
+
+            fn decode(dec: &mut AbiDecoder<'a>) -> Result<Self, AbiError> {
+                dec.$get()
+            }
+        }
+    };
+}
+
+impl_static!(U256, put_uint256, get_uint256);
+impl_static!(I256, put_int256, get_int256);
+impl_static!(Address, put_address, get_address);
+
+impl AbiEncode for bool {
+    const DYNAMIC: bool = false;
+
+    fn encode(&self, enc: &mut AbiEncoder) {
+        enc.put_bool(*self);
+    }
+}
+
+impl<'a> AbiDecode<'a> for bool {
+    const DYNAMIC: bool = false;
+
+    fn decode(dec: &mut AbiDecoder<'a>) -> Result<Self, AbiError> {
+        dec.get_bool()
+    }
+}
+
+impl<const N: usize> AbiEncode for FixedBytes<N> {
+    const DYNAMIC: bool = false;
+
+    fn encode(&self, enc: &mut AbiEncoder) {
+        enc.put_fixed_bytes(self);
+    }
+}
+
+impl<'a, const N: usize> AbiDecode<'a> for FixedBytes<N> {
+    const DYNAMIC: bool = false;
+
+    fn decode(dec: &mut AbiDecoder<'a>) -> Result<Self, AbiError> {
+        dec.get_fixed_bytes()
+    }
+}
+
+impl AbiEncode for [u8] {
+    const DYNAMIC: bool = true;
+
+    fn encode(&self, enc: &mut AbiEncoder) {
+        enc.put_bytes(self);
+    }
+}
+
+impl<'a> AbiDecode<'a> for &'a [u8] {
+    const DYNAMIC: bool = true;
+
+    fn decode(dec: &mut AbiDecoder<'a>) -> Result<Self, AbiError> {
+        dec.get_bytes()
+    }
+}
+
+impl AbiEncode for Bytes {
+    const DYNAMIC: bool = true;
+
+    fn encode(&self, enc: &mut AbiEncoder) {
+        enc.put_bytes(&self.0);
+    }
+}
+
+impl<'a> AbiDecode<'a> for Bytes {
+    const DYNAMIC: bool = true;
+
+    fn decode(dec: &mut AbiDecoder<'a>) -> Result<Self, AbiError> {
+        dec.get_bytes().map(Bytes::from)
+    }
+}
+
+impl AbiEncode for str {
+    const DYNAMIC: bool = true;
+
+    fn encode(&self, enc: &mut AbiEncoder) {
+        enc.put_string(self);
+    }
+}
+
+impl<'a> AbiDecode<'a> for &'a str {
+    const DYNAMIC: bool = true;
+
+    fn decode(dec: &mut AbiDecoder<'a>) -> Result<Self, AbiError> {
+        dec.get_string()
+    }
+}
+
+impl AbiEncode for String {
+    const DYNAMIC: bool = true;
+
+    fn encode(&self, enc: &mut AbiEncoder) {
+        enc.put_string(self);
+    }
+}
+
+impl<'a> AbiDecode<'a> for String {
+    const DYNAMIC: bool = true;
+
+    fn decode(dec: &mut AbiDecoder<'a>) -> Result<Self, AbiError> {
+        dec.get_string().map(str::to_owned)
+    }
+}
+
+impl<T: AbiEncode + ?Sized> AbiEncode for &T {
+    const DYNAMIC: bool = T::DYNAMIC;
+
+    fn encode(&self, enc: &mut AbiEncoder) {
+        (**self).encode(enc)
+    }
+
+    fn encode_fields(&self, enc: &mut AbiEncoder) {
+        (**self).encode_fields(enc)
+    }
+}
+
+/// Encodes a sequence of elements as a tuple of identical members: inline if
+/// they are static, otherwise as a list of offsets followed by their tails.
+fn encode_elements<T: AbiEncode>(prefix: Vec<u8>, elements: &[T]) -> Vec<u8> {
+    let mut sub = AbiEncoder::with_prefix(prefix);
+    for element in elements {
+        element.encode(&mut sub);
+    }
+    sub.finish()
+}
+
+fn decode_elements<'a, T: AbiDecode<'a>>(
+    dec: &mut AbiDecoder<'a>,
+    len: usize,
+) -> Result<Vec<T>, AbiError> {
+    let mut out = Vec::with_capacity(len.min(dec.remaining().len() / 32));
+    for _ in 0..len {
+        dec.charge(1)?;
+        out.push(T::decode(dec)?);
+    }
+    Ok(out)
+}
+
+impl<T: AbiEncode> AbiEncode for Vec<T> {
+    const DYNAMIC: bool = true;
+
+    fn encode(&self, enc: &mut AbiEncoder) {
+        let length = U256::from(self.len()).0.to_vec();
+        enc.put_tail(encode_elements(length, self));
+    }
+}
+
+impl<'a, T: AbiDecode<'a>> AbiDecode<'a> for Vec<T> {
+    const DYNAMIC: bool = true;
+
+    fn decode(dec: &mut AbiDecoder<'a>) -> Result<Self, AbiError> {
+        let mut tail = dec.get_tail()?;
+        let len = tail.get_length()?;
+        decode_elements(&mut tail.sub_frame(), len)
+    }
+}
+
+impl<T: AbiEncode, const K: usize> AbiEncode for [T; K] {
+    const DYNAMIC: bool = T::DYNAMIC && K > 0;
+
+    fn encode(&self, enc: &mut AbiEncoder) {
+        if Self::DYNAMIC {
+            enc.put_tail(encode_elements(Vec::new(), self));
+        } else {
+            for element in self {
+                element.encode(enc);
+            }
+        }
+    }
+}
+
+impl<'a, T: AbiDecode<'a>, const K: usize> AbiDecode<'a> for [T; K] {
+    const DYNAMIC: bool = T::DYNAMIC && K > 0;
+
+    fn decode(dec: &mut AbiDecoder<'a>) -> Result<Self, AbiError> {
+        let elements = if Self::DYNAMIC {
+            decode_elements(&mut dec.get_tail()?, K)?
+        } else {
+            decode_elements(dec, K)?
+        };
+        Ok(elements
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("decoded exactly {K} elements")))
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($name:ident: $t:ident),+) => {
+        impl<$($t: AbiEncode),+> AbiEncode for ($($t,)+) {
+            const DYNAMIC: bool = $($t::DYNAMIC)||+;
+
+            fn encode(&self, enc: &mut AbiEncoder) {
+                if Self::DYNAMIC {
+                    let mut sub = AbiEncoder::new();
+                    self.encode_fields(&mut sub);
+                    enc.put_tail(sub.finish());
+                } else {
+                    self.encode_fields(enc);
+                }
+            }
+
+            fn encode_fields(&self, enc: &mut AbiEncoder) {
+                let ($($name,)+) = self;
+                $($name.encode(enc);)+
+            }
+        }
+
+        impl<'a, $($t: AbiDecode<'a>),+> AbiDecode<'a> for ($($t,)+) {
+            const DYNAMIC: bool = $($t::DYNAMIC)||+;
+
+            fn decode(dec: &mut AbiDecoder<'a>) -> Result<Self, AbiError> {
+                if Self::DYNAMIC {
+                    Self::decode_fields(&mut dec.get_tail()?)
+                } else {
+                    Self::decode_fields(dec)
+                }
+            }
+
+            fn decode_fields(dec: &mut AbiDecoder<'a>) -> Result<Self, AbiError> {
+                Ok(($($t::decode(dec)?,)+))
+            }
+        }
+    };
+}
+
+impl_tuple!(a: A);
+impl_tuple!(a: A, b: B);
+impl_tuple!(a: A, b: B, c: C);
+impl_tuple!(a: A, b: B, c: C, d: D);
+impl_tuple!(a: A, b: B, c: C, d: D, e: E);
+impl_tuple!(a: A, b: B, c: C, d: D, e: E, f: F);
+impl_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G);
+impl_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H);
+impl_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I);
+impl_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J);
+impl_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K);
+impl_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `(address,address,uint256[],uint256[],bytes,bytes)`.
+    type Mixed<'a> = (Address, Address, Vec<U256>, Vec<U256>, &'a [u8], &'a [u8]);
+
+    /// `(uint64[2],(bool,string)[],uint8[][2])`.
+    type Nested = ([u64; 2], Vec<(bool, String)>, [Vec<u8>; 2]);
+
+    fn words(words: &[&str]) -> Vec<u8> {
+        hex::decode(words.concat()).unwrap()
+    }
+
+    #[test]
+    fn decodes_mixed_static_and_dynamic_params() {
+        // (address,address,uint256[],uint256[],bytes,bytes) =
+        // (0x11..11, 0x22..22, [1, 2], [3], 0xdeadbeef, 0x)
+        let data = words(&[
+            "0000000000000000000000001111111111111111111111111111111111111111",
+            "0000000000000000000000002222222222222222222222222222222222222222",
+            "00000000000000000000000000000000000000000000000000000000000000c0",
+            "0000000000000000000000000000000000000000000000000000000000000120",
+            "0000000000000000000000000000000000000000000000000000000000000160",
+            "00000000000000000000000000000000000000000000000000000000000001a0",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "0000000000000000000000000000000000000000000000000000000000000003",
+            "0000000000000000000000000000000000000000000000000000000000000004",
+            "deadbeef00000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+        ]);
+
+        let (a, b, xs, ys, c, d): Mixed = decode_params(&data).unwrap();
+        assert_eq!(a, Address::new([0x11; 20]));
+        assert_eq!(b, Address::new([0x22; 20]));
+        assert_eq!(xs, [U256::from(1u64), U256::from(2u64)]);
+        assert_eq!(ys, [U256::from(3u64)]);
+        assert_eq!(c, [0xde, 0xad, 0xbe, 0xef]);
+        assert!(d.is_empty());
+
+        assert_eq!(encode_params(&(a, b, &xs, &ys, c, d)), data);
+    }
+
+    #[test]
+    fn aliased_nested_arrays_are_rejected() {
+        // uint256[][] whose four entries all point at one inner array of
+        // four elements: 11 words of input that would decode to 20 values.
+        let data = words(&[
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000004",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+            "0000000000000000000000000000000000000000000000000000000000000004",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000000000000000000000000000000000000000000003",
+            "0000000000000000000000000000000000000000000000000000000000000004",
+        ]);
+
+        assert!(matches!(
+            decode_params::<(Vec<Vec<U256>>,)>(&data),
+            Err(AbiError::Oversized { .. })
+        ));
+    }
+
+    #[test]
+    fn fixed_arrays_and_tuples_round_trip() {
+        let value: Nested = (
+            [1, 2],
+            vec![(true, "a".to_string()), (false, "bc".to_string())],
+            [vec![1], vec![2, 3]],
+        );
+        let data = encode_params(&value);
+        let decoded: Nested = decode_params(&data).unwrap();
+        assert_eq!(decoded, value);
+    }
+}
@@ -0,0 +1,379 @@
+//! Safe, bounds checked ABI decoder over a byte slice.
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::str;
+
+use super::error::AbiError;
+use super::types::{Address, FixedBytes, I256, U256};
+
+const WORD: usize = 32;
+
+/// Reads standard ABI encoded values from a borrowed frame.
+///
+/// Values are read in order from the head; `bytes` and `string` follow their
+/// offset word into the tail and are returned as slices of the input, so no
+/// data is copied. Every read is bounds checked and padding must be zero.
+///
+/// Offsets may point anywhere in the frame, so several of them can alias
+/// one tail and make a small input decode into a huge value. Array elements
+/// and `bytes` contents are therefore charged against a budget of one word
+/// per word of input, shared by every decoder derived from this one; well
+/// formed data never exceeds it.
+#[derive(Debug, Clone)]
+pub struct AbiDecoder<'a> {
+    frame: &'a [u8],
+    pos: usize,
+    base: usize,
+    budget: Rc<Cell<usize>>,
+}
+
+impl<'a> AbiDecoder<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            frame: data,
+            pos: 0,
+            base: 0,
+            budget: Rc::new(Cell::new(data.len() / WORD)),
+        }
+    }
+
+    /// Checks that `data` starts with `expected` and returns a decoder over the
+    /// arguments that follow it.
+    pub fn with_funsel(data: &'a [u8], expected: u32) -> Result<Self, AbiError> {
+        let found = Self::peek_funsel(data).ok_or(AbiError::Truncated {
+            offset: 0,
+            needed: 4,
+            available: data.len(),
+        })?;
+        if found != expected {
+            return Err(AbiError::FunselMismatch { expected, found });
+        }
+        Ok(Self {
+            frame: &data[4..],
+            pos: 0,
+            base: 4,
+            budget: Rc::new(Cell::new((data.len() - 4) / WORD)),
+        })
+    }
+
+    /// Returns the function selector at the start of `data`, in the same byte
+    /// order as `cmt_abi_peek_funsel`.
+    pub fn peek_funsel(data: &[u8]) -> Option<u32> {
+        data.get(..4)
+            .map(|selector| u32::from_le_bytes(selector.try_into().unwrap()))
+    }
+
+    /// Bytes of the frame not yet consumed by head reads.
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.frame[self.pos..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.frame.len()
+    }
+
+    pub fn get_uint256(&mut self) -> Result<U256, AbiError> {
+        self.get_word().map(|word| U256(*word))
+    }
+
+    pub fn get_int256(&mut self) -> Result<I256, AbiError> {
+        self.get_word().map(|word| I256(*word))
+    }
+
+    pub fn get_u64(&mut self) -> Result<u64, AbiError> {
+        self.get_uint()
+    }
+
+    pub fn get_bool(&mut self) -> Result<bool, AbiError> {
+        let offset = self.offset();
+        let word = self.get_word()?;
+        if word[..WORD - 1].iter().any(|&b| b != 0) || word[WORD - 1] > 1 {
+            return Err(AbiError::NonCanonical {
+                offset,
+                kind: "bool",
+            });
+        }
+        Ok(word[WORD - 1] == 1)
+    }
+
+    pub fn get_address(&mut self) -> Result<Address, AbiError> {
+        let offset = self.offset();
+        let word = self.get_word()?;
+        let (padding, address) = word.split_at(WORD - 20);
+        if padding.iter().any(|&b| b != 0) {
+            return Err(AbiError::NonCanonical {
+                offset,
+                kind: "address",
+            });
+        }
+        Ok(Address(address.try_into().unwrap()))
+    }
+
+    /// Decodes a `bytesN` value; the unused low-order bytes must be zero.
+    pub fn get_fixed_bytes<const N: usize>(&mut self) -> Result<FixedBytes<N>, AbiError> {
+        const { assert!(N >= 1 && N <= WORD, "bytesN requires 1 <= N <= 32") };
+        let offset = self.offset();
+        let word = self.get_word()?;
+        let (value, padding) = word.split_at(N);
+        if padding.iter().any(|&b| b != 0) {
+            return Err(AbiError::NonCanonical {
+                offset,
+                kind: "bytesN",
+            });
+        }
+        Ok(FixedBytes(value.try_into().unwrap()))
+    }
+
+    /// Decodes a dynamic `bytes` value, borrowing its contents from the input.
+    pub fn get_bytes(&mut self) -> Result<&'a [u8], AbiError> {
+        let mut tail = self.get_tail()?;
+        let length = tail.get_length()?;
+        tail.charge(length.div_ceil(WORD))?;
+        let start = tail.pos;
+        let data = tail.take(length)?;
+
+        let padded = length.div_ceil(WORD) * WORD;
+        let padding_end = (start + padded).min(tail.frame.len());
+        if tail.frame[start + length..padding_end]
+            .iter()
+            .any(|&b| b != 0)
+        {
+            return Err(AbiError::NonCanonical {
+                offset: tail.base + start + length,
+                kind: "bytes",
+            });
+        }
+        Ok(data)
+    }
+
+    /// Decodes a dynamic `string` value, borrowing its contents from the input.
+    pub fn get_string(&mut self) -> Result<&'a str, AbiError> {
+        let offset = self.offset();
+        let data = self.get_bytes()?;
+        str::from_utf8(data).map_err(|_| AbiError::InvalidUtf8 { offset })
+    }
+
+    /// Reads an offset word from the head and returns a decoder positioned at
+    /// the start of the dynamic value it points to.
+    pub fn get_tail(&mut self) -> Result<AbiDecoder<'a>, AbiError> {
+        let offset = self.offset();
+        let word = self.get_word()?;
+        let start = word_to_usize(word)
+            .filter(|&start| start <= self.frame.len())
+            .ok_or_else(|| AbiError::BadOffset {
+                offset,
+                value: format!("0x{}", hex::encode(word)),
+            })?;
+        Ok(Self {
+            frame: &self.frame[start..],
+            pos: 0,
+            base: self.base + start,
+            budget: Rc::clone(&self.budget),
+        })
+    }
+
+    /// Returns a decoder whose frame starts at the current position, e.g. the
+    /// elements of an array after its length word.
+    pub(crate) fn sub_frame(&self) -> AbiDecoder<'a> {
+        Self {
+            frame: &self.frame[self.pos..],
+            pos: 0,
+            base: self.offset(),
+            budget: Rc::clone(&self.budget),
+        }
+    }
+
+    /// Reads a length word, rejecting values larger than the remaining data.
+    pub(crate) fn get_length(&mut self) -> Result<usize, AbiError> {
+        let offset = self.offset();
+        let word = self.get_word()?;
+        word_to_usize(word)
+            .filter(|&length| length <= self.frame.len() - self.pos)
+            .ok_or_else(|| AbiError::BadOffset {
+                offset,
+                value: format!("0x{}", hex::encode(word)),
+            })
+    }
+
+    /// Takes `words` from the shared budget before decoding that many words
+    /// of array elements or `bytes` contents.
+    pub(crate) fn charge(&self, words: usize) -> Result<(), AbiError> {
+        let left = self.budget.get();
+        if words > left {
+            return Err(AbiError::Oversized {
+                offset: self.offset(),
+            });
+        }
+        self.budget.set(left - words);
+        Ok(())
+    }
+
+    pub(crate) fn get_word(&mut self) -> Result<&'a [u8; WORD], AbiError> {
+        self.take(WORD).map(|word| word.try_into().unwrap())
+    }
+
+    /// Absolute position of the next read within the original input.
+    pub(crate) fn offset(&self) -> usize {
+        self.base + self.pos
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], AbiError> {
+        let available = self.frame.len() - self.pos;
+        if n > available {
+            return Err(AbiError::Truncated {
+                offset: self.offset(),
+                needed: n,
+                available,
+            });
+        }
+        let data = &self.frame[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(data)
+    }
+}
+
+fn word_to_usize(word: &[u8; WORD]) -> Option<usize> {
+    const SIZE: usize = size_of::<usize>();
+    let (high, low) = word.split_at(WORD - SIZE);
+    if high.iter().any(|&b| b != 0) {
+        return None;
+    }
+    Some(usize::from_be_bytes(low.try_into().unwrap()))
+}
+
+/// Whether `word` holds an unsigned value that fits in `bits` bits.
+pub(crate) fn fits_uint(word: &[u8; WORD], bits: usize) -> bool {
+    let unused = WORD - bits / 8;
+    word[..unused].iter().all(|&b| b == 0)
+}
+
+/// Whether `word` holds a two's complement value that fits in `bits` bits,
+/// i.e. every byte above the value is a copy of its sign bit.
+pub(crate) fn fits_int(word: &[u8; WORD], bits: usize) -> bool {
+    let unused = WORD - bits / 8;
+    let fill = if word[unused] & 0x80 != 0 { 0xff } else { 0x00 };
+    word[..unused].iter().all(|&b| b == fill)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(words: &[&str]) -> Vec<u8> {
+        hex::decode(words.concat()).unwrap()
+    }
+
+    #[test]
+    fn truncated_word() {
+        let data = [0u8; 31];
+        assert_eq!(
+            AbiDecoder::new(&data).get_uint256(),
+            Err(AbiError::Truncated {
+                offset: 0,
+                needed: 32,
+                available: 31,
+            })
+        );
+    }
+
+    #[test]
+    fn offset_past_the_end() {
+        let data = words(&["0000000000000000000000000000000000000000000000000000000000000040"]);
+        assert!(matches!(
+            AbiDecoder::new(&data).get_bytes(),
+            Err(AbiError::BadOffset { offset: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn length_past_the_end() {
+        let data = words(&[
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000021",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+        ]);
+        assert!(matches!(
+            AbiDecoder::new(&data).get_bytes(),
+            Err(AbiError::BadOffset { offset: 32, .. })
+        ));
+    }
+
+    #[test]
+    fn non_canonical_bool() {
+        let data = words(&["0000000000000000000000000000000000000000000000000000000000000002"]);
+        assert_eq!(
+            AbiDecoder::new(&data).get_bool(),
+            Err(AbiError::NonCanonical {
+                offset: 0,
+                kind: "bool",
+            })
+        );
+    }
+
+    #[test]
+    fn non_canonical_address() {
+        let data = words(&["0000000000000000000000011111111111111111111111111111111111111111"]);
+        assert_eq!(
+            AbiDecoder::new(&data).get_address(),
+            Err(AbiError::NonCanonical {
+                offset: 0,
+                kind: "address",
+            })
+        );
+    }
+
+    #[test]
+    fn non_canonical_fixed_bytes() {
+        let data = words(&["aabbccdd00000000000000000000000000000000000000000000000000000001"]);
+        assert_eq!(
+            AbiDecoder::new(&data).get_fixed_bytes::<4>(),
+            Err(AbiError::NonCanonical {
+                offset: 0,
+                kind: "bytesN",
+            })
+        );
+    }
+
+    #[test]
+    fn non_canonical_bytes_padding() {
+        let data = words(&[
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "abcd000000000000000000000000000000000000000000000000000000000001",
+        ]);
+        assert_eq!(
+            AbiDecoder::new(&data).get_bytes(),
+            Err(AbiError::NonCanonical {
+                offset: 66,
+                kind: "bytes",
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let data = words(&[
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "c328000000000000000000000000000000000000000000000000000000000000",
+        ]);
+        assert_eq!(
+            AbiDecoder::new(&data).get_string(),
+            Err(AbiError::InvalidUtf8 { offset: 0 })
+        );
+    }
+
+    #[test]
+    fn funsel_mismatch() {
+        let data = hex::decode("a9059cbb").unwrap();
+        let expected = u32::from_le_bytes([0x23, 0xb8, 0x72, 0xdd]);
+        assert_eq!(
+            AbiDecoder::with_funsel(&data, expected).err(),
+            Some(AbiError::FunselMismatch {
+                expected,
+                found: u32::from_le_bytes([0xa9, 0x05, 0x9c, 0xbb]),
+            })
+        );
+    }
+}
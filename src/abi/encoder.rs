@@ -0,0 +1,230 @@
+//! Safe ABI encoder that accumulates into a growable `Vec<u8>`.
+
+use super::types::{Address, FixedBytes, I256, U256};
+
+const WORD: usize = 32;
+
+/// Builds standard ABI encoded data without a preallocated `cmt_buf_t`.
+///
+/// Static values are written to the head in call order. Dynamic values
+/// (`bytes`, `string`, arrays, dynamic tuples) leave an offset word in the head and append their
+/// contents to the tail; the offsets are resolved by [`AbiEncoder::finish`],
+/// so the output matches what `cmt_abi_put_bytes_s`/`cmt_abi_put_bytes_d`
+/// produce for the same sequence of values.
+#[derive(Debug, Clone, Default)]
+pub struct AbiEncoder {
+    prefix: Vec<u8>,
+    head: Vec<u8>,
+    tail: Vec<u8>,
+    offsets: Vec<(usize, usize)>,
+}
+
+impl AbiEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a call payload with a function selector, as `cmt_abi_put_funsel`
+    /// followed by `cmt_abi_mark_frame` would. The selector uses the same
+    /// in-memory byte order as [`crate::abi::funsel`] and
+    /// [`crate::keccak::funsel`].
+    pub fn with_funsel(funsel: u32) -> Self {
+        Self::with_prefix(funsel.to_le_bytes().to_vec())
+    }
+
+    /// Starts a frame after `prefix`; offsets are relative to the end of it.
+    pub(crate) fn with_prefix(prefix: Vec<u8>) -> Self {
+        Self {
+            prefix,
+            ..Self::default()
+        }
+    }
+
+    pub fn put_uint<T: Into<U256>>(&mut self, value: T) -> &mut Self {
+        self.put_uint256(&value.into())
+    }
+
+    pub fn put_uint256(&mut self, value: &U256) -> &mut Self {
+        self.put_word(value.0)
+    }
+
+    pub fn put_int<T: Into<I256>>(&mut self, value: T) -> &mut Self {
+        self.put_int256(&value.into())
+    }
+
+    pub fn put_int256(&mut self, value: &I256) -> &mut Self {
+        self.put_word(value.0)
+    }
+
+    pub fn put_bool(&mut self, value: bool) -> &mut Self {
+        self.put_uint(u8::from(value))
+    }
+
+    pub fn put_address(&mut self, value: &Address) -> &mut Self {
+        let mut word = [0u8; WORD];
+        word[WORD - 20..].copy_from_slice(&value.0);
+        self.put_word(word)
+    }
+
+    /// Encodes a `bytesN` value, right padded with zeros.
+    pub fn put_fixed_bytes<const N: usize>(&mut self, value: &FixedBytes<N>) -> &mut Self {
+        const { assert!(N >= 1 && N <= WORD, "bytesN requires 1 <= N <= 32") };
+        let mut word = [0u8; WORD];
+        word[..N].copy_from_slice(&value.0);
+        self.put_word(word)
+    }
+
+    pub fn put_bytes(&mut self, value: &[u8]) -> &mut Self {
+        self.put_tail(encode_bytes_tail(value))
+    }
+
+    pub fn put_string(&mut self, value: &str) -> &mut Self {
+        self.put_bytes(value.as_bytes())
+    }
+
+    /// Length of the encoded data so far, excluding pending tails.
+    pub fn head_len(&self) -> usize {
+        self.prefix.len() + self.head.len()
+    }
+
+    /// Resolves the dynamic offsets and returns the encoded bytes.
+    pub fn finish(self) -> Vec<u8> {
+        let Self {
+            prefix,
+            mut head,
+            tail,
+            offsets,
+        } = self;
+
+        let head_len = head.len();
+        for (slot, position) in offsets {
+            let offset = U256::from(head_len + position);
+            head[slot..slot + WORD].copy_from_slice(&offset.0);
+        }
+
+        let mut out = prefix;
+        out.reserve(head.len() + tail.len());
+        out.extend_from_slice(&head);
+        out.extend_from_slice(&tail);
+        out
+    }
+
+    pub(crate) fn put_word(&mut self, word: [u8; WORD]) -> &mut Self {
+        self.head.extend_from_slice(&word);
+        self
+    }
+
+    /// Writes an offset placeholder to the head and appends `data`, an already
+    /// encoded dynamic value, to the tail.
+    pub fn put_tail(&mut self, data: Vec<u8>) -> &mut Self {
+        self.offsets.push((self.head.len(), self.tail.len()));
+        self.head.extend_from_slice(&[0u8; WORD]);
+        self.tail.extend_from_slice(&data);
+        self
+    }
+}
+
+/// Encodes the tail of a `bytes`/`string` value: its length followed by the
+/// data right padded to a multiple of 32 bytes.
+pub(crate) fn encode_bytes_tail(value: &[u8]) -> Vec<u8> {
+    let padded = value.len().div_ceil(WORD) * WORD;
+    let mut out = Vec::with_capacity(WORD + padded);
+    out.extend_from_slice(&U256::from(value.len()).0);
+    out.extend_from_slice(value);
+    out.resize(WORD + padded, 0);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::c_void;
+    use std::ptr;
+
+    use super::*;
+    use crate::{abi, buf, cmt_abi_bytes_t, cmt_buf_t};
+
+    fn empty_buf() -> cmt_buf_t {
+        cmt_buf_t {
+            begin: ptr::null_mut(),
+            end: ptr::null_mut(),
+        }
+    }
+
+    /// Runs `f` over a libcmt buffer with a frame marked at its start and
+    /// returns the bytes written.
+    fn libcmt_encode(funsel: Option<u32>, f: impl FnOnce(&mut cmt_buf_t, &cmt_buf_t)) -> Vec<u8> {
+        let mut mem = vec![0u8; 4096];
+        let mut me = empty_buf();
+        buf::init(&mut me, mem.len(), mem.as_mut_ptr());
+        let start = me.begin as usize;
+        if let Some(funsel) = funsel {
+            abi::put_funsel(&mut me, funsel).unwrap();
+        }
+        let mut frame = empty_buf();
+        abi::mark_frame(&mut me, &mut frame).unwrap();
+        f(&mut me, &frame);
+        let len = me.begin as usize - start;
+        mem.truncate(len);
+        mem
+    }
+
+    fn c_bytes(data: &[u8]) -> cmt_abi_bytes_t {
+        cmt_abi_bytes_t {
+            length: data.len(),
+            data: data.as_ptr() as *mut c_void,
+        }
+    }
+
+    #[test]
+    fn static_values_match_libcmt() {
+        let address = Address::new([0x11; 20]);
+        let value = 0x0123_4567_89ab_cdefu64;
+
+        let mut enc = AbiEncoder::new();
+        enc.put_uint(value).put_bool(true).put_address(&address);
+
+        let expected = libcmt_encode(None, |me, _| {
+            abi::put_uint(me, size_of::<u64>(), &value as *const u64 as *const c_void).unwrap();
+            abi::put_bool(me, true).unwrap();
+            abi::put_address(me, &address.into()).unwrap();
+        });
+        assert_eq!(enc.finish(), expected);
+    }
+
+    #[test]
+    fn dynamic_values_match_libcmt() {
+        let address = Address::new([0x22; 20]);
+        let first = b"hello, libcmt".as_slice();
+        let second = "a string longer than one 32-byte ABI word".as_bytes();
+
+        let funsel = crate::keccak::funsel("f(bytes,address,string)");
+        let mut enc = AbiEncoder::with_funsel(funsel);
+        enc.put_bytes(first)
+            .put_address(&address)
+            .put_string(std::str::from_utf8(second).unwrap());
+
+        let expected = libcmt_encode(Some(funsel), |me, frame| {
+            let mut first_offset = empty_buf();
+            let mut second_offset = empty_buf();
+            abi::put_bytes_s(me, &mut first_offset).unwrap();
+            abi::put_address(me, &address.into()).unwrap();
+            abi::put_bytes_s(me, &mut second_offset).unwrap();
+            abi::put_bytes_d(me, &mut first_offset, frame, &c_bytes(first)).unwrap();
+            abi::put_bytes_d(me, &mut second_offset, frame, &c_bytes(second)).unwrap();
+        });
+        assert_eq!(enc.finish(), expected);
+    }
+
+    #[test]
+    fn empty_bytes_match_libcmt() {
+        let mut enc = AbiEncoder::new();
+        enc.put_bytes(&[]);
+
+        let expected = libcmt_encode(None, |me, frame| {
+            let mut offset = empty_buf();
+            abi::put_bytes_s(me, &mut offset).unwrap();
+            abi::put_bytes_d(me, &mut offset, frame, &c_bytes(&[])).unwrap();
+        });
+        assert_eq!(enc.finish(), expected);
+    }
+}
@@ -0,0 +1,90 @@
+//! Errors reported by the safe ABI decoder.
+
+use std::{error, fmt, io};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbiError {
+    /// Fewer bytes than required at `offset`.
+    Truncated {
+        offset: usize,
+        needed: usize,
+        available: usize,
+    },
+    /// A dynamic offset or length word that points outside the data.
+    BadOffset { offset: usize, value: String },
+    /// Padding bytes that a canonical encoder would have zeroed.
+    NonCanonical { offset: usize, kind: &'static str },
+    /// A word that does not fit the requested integer type.
+    Overflow { offset: usize, bits: usize },
+    /// An integer width that is not a multiple of 8 between 8 and 256.
+    InvalidWidth { bits: usize },
+    /// Offsets that alias each other so that the data would decode to more
+    /// values than it holds.
+    Oversized { offset: usize },
+    /// A `string` whose contents are not valid UTF-8.
+    InvalidUtf8 { offset: usize },
+    /// The payload does not start with the expected function selector.
+    FunselMismatch { expected: u32, found: u32 },
+    /// The payload starts with a function selector no call variant accepts.
+    UnknownFunsel { found: u32 },
+    /// A dynamically typed value that does not match its declared ABI type.
+    TypeMismatch { expected: String, found: String },
+}
+
+impl fmt::Display for AbiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated {
+                offset,
+                needed,
+                available,
+            } => write!(
+                f,
+                "truncated ABI data at offset {offset}: need {needed} bytes, {available} available"
+            ),
+            Self::BadOffset { offset, value } => {
+                write!(f, "bad ABI offset {value} at offset {offset}")
+            }
+            Self::NonCanonical { offset, kind } => {
+                write!(f, "non-canonical {kind} padding at offset {offset}")
+            }
+            Self::Overflow { offset, bits } => {
+                write!(
+                    f,
+                    "ABI value at offset {offset} does not fit in {bits} bits"
+                )
+            }
+            Self::InvalidWidth { bits } => write!(
+                f,
+                "invalid integer width {bits}, expected a multiple of 8 between 8 and 256"
+            ),
+            Self::Oversized { offset } => write!(
+                f,
+                "ABI data at offset {offset} decodes to more values than the input holds"
+            ),
+            Self::InvalidUtf8 { offset } => write!(f, "invalid UTF-8 string at offset {offset}"),
+            Self::FunselMismatch { expected, found } => write!(
+                f,
+                "function selector mismatch: expected 0x{}, found 0x{}",
+                hex::encode(expected.to_le_bytes()),
+                hex::encode(found.to_le_bytes())
+            ),
+            Self::UnknownFunsel { found } => write!(
+                f,
+                "unknown function selector 0x{}",
+                hex::encode(found.to_le_bytes())
+            ),
+            Self::TypeMismatch { expected, found } => {
+                write!(f, "ABI type mismatch: expected {expected}, found {found}")
+            }
+        }
+    }
+}
+
+impl error::Error for AbiError {}
+
+impl From<AbiError> for io::Error {
+    fn from(e: AbiError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
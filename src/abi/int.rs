@@ -0,0 +1,194 @@
+//! Signed `intN` encoding and decoding with two's complement sign extension.
+
+use super::codec::{AbiDecode, AbiEncode};
+use super::decoder::{AbiDecoder, fits_int};
+use super::encoder::AbiEncoder;
+use super::error::AbiError;
+use super::types::I256;
+
+/// A native signed integer that maps to a Solidity `intN`.
+pub trait IntLike: Copy + Into<I256> {
+    /// The Solidity width `N` of the type.
+    const BITS: usize;
+
+    /// Truncates a sign extended word to the type; callers check the range.
+    fn from_word(word: &[u8; 32]) -> Self;
+}
+
+macro_rules! impl_int_like {
+    ($($t:ty),*) => {$(
+        impl IntLike for $t {
+            const BITS: usize = <$t>::BITS as usize;
+
+            fn from_word(word: &[u8; 32]) -> Self {
+                const SIZE: usize = size_of::<$t>();
+                <$t>::from_be_bytes(word[32 - SIZE..].try_into().unwrap())
+            }
+        }
+
+        impl AbiEncode for $t {
+            const DYNAMIC: bool = false;
+
+            fn encode(&self, enc: &mut AbiEncoder) {
+                enc.put_int(*self);
+            }
+        }
+
+        impl<'a> AbiDecode<'a> for $t {
+            const DYNAMIC: bool = false;
+
+            fn decode(dec: &mut AbiDecoder<'a>) -> Result<Self, AbiError> {
+                dec.get_int()
+            }
+        }
+    )*};
+}
+
+impl_int_like!(i8, i16, i32, i64, i128);
+
+impl IntLike for I256 {
+    const BITS: usize = 256;
+
+    fn from_word(word: &[u8; 32]) -> Self {
+        I256(*word)
+    }
+}
+
+/// Encodes `value` as a sign extended 32-byte word.
+pub fn encode_int<T: IntLike>(value: T) -> [u8; 32] {
+    value.into().0
+}
+
+/// Decodes a word into `T`, rejecting words whose high bytes are not a
+/// consistent sign extension of the value.
+pub fn decode_int<T: IntLike>(word: &[u8; 32]) -> Result<T, AbiError> {
+    if !fits_int(word, T::BITS) {
+        return Err(AbiError::Overflow {
+            offset: 0,
+            bits: T::BITS,
+        });
+    }
+    Ok(T::from_word(word))
+}
+
+impl AbiEncoder {
+    /// Encodes an `intN` held in an [`I256`], failing if it does not fit in
+    /// `bits` bits.
+    pub fn put_int_n(&mut self, bits: usize, value: &I256) -> Result<&mut Self, AbiError> {
+        check_bits(bits)?;
+        if !fits_int(&value.0, bits) {
+            return Err(AbiError::Overflow {
+                offset: self.head_len(),
+                bits,
+            });
+        }
+        Ok(self.put_int256(value))
+    }
+}
+
+impl AbiDecoder<'_> {
+    /// Decodes an `intN` into the native type `T`.
+    pub fn get_int<T: IntLike>(&mut self) -> Result<T, AbiError> {
+        let offset = self.offset();
+        let word = self.get_word()?;
+        decode_int(word).map_err(|_| AbiError::Overflow {
+            offset,
+            bits: T::BITS,
+        })
+    }
+
+    /// Decodes an `intN` of any width, e.g. `int24`, into an [`I256`].
+    pub fn get_int_n(&mut self, bits: usize) -> Result<I256, AbiError> {
+        check_bits(bits)?;
+        let offset = self.offset();
+        let word = self.get_word()?;
+        if !fits_int(word, bits) {
+            return Err(AbiError::Overflow { offset, bits });
+        }
+        Ok(I256(*word))
+    }
+}
+
+fn check_bits(bits: usize) -> Result<(), AbiError> {
+    if !(8..=256).contains(&bits) || !bits.is_multiple_of(8) {
+        return Err(AbiError::InvalidWidth { bits });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(hex: &str) -> [u8; 32] {
+        hex::decode(hex).unwrap().try_into().unwrap()
+    }
+
+    const MINUS_ONE: &str = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+
+    #[test]
+    fn int8_range() {
+        let cases = [
+            (MINUS_ONE, -1),
+            (
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff80",
+                -128,
+            ),
+            (
+                "000000000000000000000000000000000000000000000000000000000000007f",
+                127,
+            ),
+        ];
+        for (hex, value) in cases {
+            assert_eq!(decode_int::<i8>(&word(hex)), Ok(value));
+            assert_eq!(encode_int(value), word(hex));
+        }
+    }
+
+    #[test]
+    fn int8_rejects_missing_sign_extension() {
+        // 128 is a valid uint8 but not an int8.
+        let positive = word("0000000000000000000000000000000000000000000000000000000000000080");
+        assert_eq!(
+            decode_int::<i8>(&positive),
+            Err(AbiError::Overflow { offset: 0, bits: 8 })
+        );
+        // A positive int8 whose high bytes are 0xff.
+        let padded = word("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f");
+        assert_eq!(
+            decode_int::<i8>(&padded),
+            Err(AbiError::Overflow { offset: 0, bits: 8 })
+        );
+    }
+
+    #[test]
+    fn int24() {
+        let data = [
+            word("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffff800000"),
+            word("0000000000000000000000000000000000000000000000000000000000800000"),
+        ]
+        .concat();
+        let mut dec = AbiDecoder::new(&data);
+        assert_eq!(dec.get_int_n(24), Ok(I256::from(-0x80_0000i32)));
+        assert_eq!(
+            dec.get_int_n(24),
+            Err(AbiError::Overflow {
+                offset: 32,
+                bits: 24,
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_width() {
+        let mut enc = AbiEncoder::new();
+        assert_eq!(
+            enc.put_int_n(12, &I256::from(1i8)).err(),
+            Some(AbiError::InvalidWidth { bits: 12 })
+        );
+        assert_eq!(
+            AbiDecoder::new(&word(MINUS_ONE)).get_int_n(264),
+            Err(AbiError::InvalidWidth { bits: 264 })
+        );
+    }
+}
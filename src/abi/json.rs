@@ -0,0 +1,766 @@
+//! Runtime ABI encoding and decoding driven by a Solidity ABI JSON file.
+//!
+//! [`Contract::from_json`] accepts the plain ABI array emitted by `solc` as
+//! well as Hardhat/Foundry artifacts with an `abi` key. Functions and events
+//! are looked up by name or selector and their arguments are encoded and
+//! decoded as dynamically typed [`AbiValue`]s, so generic tools can inspect
+//! any payload without generated code.
+
+use std::{fmt, io};
+
+use serde::Deserialize;
+
+use super::decoder::{AbiDecoder, fits_int, fits_uint};
+use super::encoder::AbiEncoder;
+use super::error::AbiError;
+use super::types::{Address, B256, I256, U256};
+use crate::keccak;
+
+const WORD: usize = 32;
+
+/// A Solidity type as declared in an ABI JSON file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamType {
+    Address,
+    Bool,
+    Uint(usize),
+    Int(usize),
+    FixedBytes(usize),
+    Bytes,
+    String,
+    Array(Box<ParamType>),
+    FixedArray(Box<ParamType>, usize),
+    Tuple(Vec<ParamType>),
+}
+
+impl ParamType {
+    /// Parses a type such as `uint256`, `bytes32[]` or `tuple[2]`; tuple
+    /// members come from the `components` of the JSON parameter.
+    fn parse(ty: &str, components: &[RawParam]) -> io::Result<Self> {
+        if let Some(inner) = ty.strip_suffix("[]") {
+            return Ok(Self::Array(Box::new(Self::parse(inner, components)?)));
+        }
+        if let Some(open) = ty.strip_suffix(']').and_then(|t| t.rfind('[')) {
+            let len = ty[open + 1..ty.len() - 1]
+                .parse()
+                .map_err(|_| invalid_type(ty))?;
+            let inner = Self::parse(&ty[..open], components)?;
+            return Ok(Self::FixedArray(Box::new(inner), len));
+        }
+
+        let sized = |prefix: &str, min: usize, max: usize, step: usize| {
+            ty.strip_prefix(prefix)
+                .map(|n| if n.is_empty() { Ok(max) } else { n.parse() })
+                .and_then(Result::ok)
+                .filter(|&n| n >= min && n <= max && n % step == 0)
+        };
+        Ok(match ty {
+            "address" => Self::Address,
+            "bool" => Self::Bool,
+            "bytes" => Self::Bytes,
+            "string" => Self::String,
+            "tuple" => Self::Tuple(
+                components
+                    .iter()
+                    .map(|c| Self::parse(&c.ty, &c.components))
+                    .collect::<io::Result<_>>()?,
+            ),
+            _ if ty.starts_with("uint") => {
+                Self::Uint(sized("uint", 8, 256, 8).ok_or_else(|| invalid_type(ty))?)
+            }
+            _ if ty.starts_with("int") => {
+                Self::Int(sized("int", 8, 256, 8).ok_or_else(|| invalid_type(ty))?)
+            }
+            _ if ty.starts_with("bytes") => {
+                Self::FixedBytes(sized("bytes", 1, 32, 1).ok_or_else(|| invalid_type(ty))?)
+            }
+            _ => return Err(invalid_type(ty)),
+        })
+    }
+
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            Self::Bytes | Self::String | Self::Array(_) => true,
+            Self::FixedArray(inner, len) => *len > 0 && inner.is_dynamic(),
+            Self::Tuple(members) => members.iter().any(Self::is_dynamic),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for ParamType {
+    /// Writes the canonical type name used in signatures.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Address => write!(f, "address"),
+            Self::Bool => write!(f, "bool"),
+            Self::Uint(bits) => write!(f, "uint{bits}"),
+            Self::Int(bits) => write!(f, "int{bits}"),
+            Self::FixedBytes(n) => write!(f, "bytes{n}"),
+            Self::Bytes => write!(f, "bytes"),
+            Self::String => write!(f, "string"),
+            Self::Array(inner) => write!(f, "{inner}[]"),
+            Self::FixedArray(inner, len) => write!(f, "{inner}[{len}]"),
+            Self::Tuple(members) => {
+                write!(f, "(")?;
+                for (i, member) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{member}")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+fn invalid_type(ty: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("unsupported ABI type: {ty}"),
+    )
+}
+
+/// A dynamically typed ABI value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbiValue {
+    Address(Address),
+    Bool(bool),
+    Uint(U256),
+    Int(I256),
+    FixedBytes(Vec<u8>),
+    Bytes(Vec<u8>),
+    String(String),
+    Array(Vec<AbiValue>),
+    FixedArray(Vec<AbiValue>),
+    Tuple(Vec<AbiValue>),
+}
+
+impl AbiValue {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Address(_) => "address",
+            Self::Bool(_) => "bool",
+            Self::Uint(_) => "uint",
+            Self::Int(_) => "int",
+            Self::FixedBytes(_) => "bytesN",
+            Self::Bytes(_) => "bytes",
+            Self::String(_) => "string",
+            Self::Array(_) => "array",
+            Self::FixedArray(_) => "fixed array",
+            Self::Tuple(_) => "tuple",
+        }
+    }
+}
+
+impl fmt::Display for AbiValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |f: &mut fmt::Formatter<'_>, open, values: &[AbiValue], close| {
+            write!(f, "{open}")?;
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{value}")?;
+            }
+            write!(f, "{close}")
+        };
+        match self {
+            Self::Address(address) => write!(f, "{address}"),
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Uint(value) => write!(f, "{value}"),
+            Self::Int(value) => write!(f, "{value}"),
+            Self::FixedBytes(data) | Self::Bytes(data) => write!(f, "0x{}", hex::encode(data)),
+            Self::String(value) => write!(f, "{value:?}"),
+            Self::Array(values) | Self::FixedArray(values) => list(f, "[", values, "]"),
+            Self::Tuple(values) => list(f, "(", values, ")"),
+        }
+    }
+}
+
+/// A named function, event or tuple member.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Param {
+    pub name: String,
+    pub kind: ParamType,
+    pub indexed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub name: String,
+    pub inputs: Vec<Param>,
+    pub outputs: Vec<Param>,
+    selector: u32,
+}
+
+impl Function {
+    /// The canonical signature, e.g. `transfer(address,uint256)`.
+    pub fn signature(&self) -> String {
+        signature(&self.name, &self.inputs)
+    }
+
+    /// The function selector, in the byte order used by [`keccak::funsel`].
+    pub fn selector(&self) -> u32 {
+        self.selector
+    }
+
+    /// Encodes a call: the selector followed by `args`.
+    pub fn encode_input(&self, args: &[AbiValue]) -> Result<Vec<u8>, AbiError> {
+        let mut enc = AbiEncoder::with_funsel(self.selector);
+        encode_params(&mut enc, &self.inputs, args)?;
+        Ok(enc.finish())
+    }
+
+    /// Checks the selector of `data` and decodes the call arguments.
+    pub fn decode_input(&self, data: &[u8]) -> Result<Vec<AbiValue>, AbiError> {
+        decode_params(
+            &mut AbiDecoder::with_funsel(data, self.selector)?,
+            &self.inputs,
+        )
+    }
+
+    pub fn encode_output(&self, values: &[AbiValue]) -> Result<Vec<u8>, AbiError> {
+        let mut enc = AbiEncoder::new();
+        encode_params(&mut enc, &self.outputs, values)?;
+        Ok(enc.finish())
+    }
+
+    pub fn decode_output(&self, data: &[u8]) -> Result<Vec<AbiValue>, AbiError> {
+        decode_params(&mut AbiDecoder::new(data), &self.outputs)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub name: String,
+    pub inputs: Vec<Param>,
+    pub anonymous: bool,
+    topic: B256,
+}
+
+impl Event {
+    /// The canonical signature, e.g. `Transfer(address,address,uint256)`.
+    pub fn signature(&self) -> String {
+        signature(&self.name, &self.inputs)
+    }
+
+    /// The keccak hash of the signature, emitted as the first log topic of
+    /// non-anonymous events.
+    pub fn topic(&self) -> B256 {
+        self.topic
+    }
+
+    /// Encodes the non-indexed inputs, i.e. the data section of the log.
+    pub fn encode_data(&self, values: &[AbiValue]) -> Result<Vec<u8>, AbiError> {
+        let params: Vec<Param> = self.inputs.iter().filter(|p| !p.indexed).cloned().collect();
+        let mut enc = AbiEncoder::new();
+        encode_params(&mut enc, &params, values)?;
+        Ok(enc.finish())
+    }
+
+    /// Decodes the data section of the log into the non-indexed inputs.
+    pub fn decode_data(&self, data: &[u8]) -> Result<Vec<AbiValue>, AbiError> {
+        let params: Vec<Param> = self.inputs.iter().filter(|p| !p.indexed).cloned().collect();
+        decode_params(&mut AbiDecoder::new(data), &params)
+    }
+}
+
+/// The functions and events of a contract ABI.
+#[derive(Debug, Clone, Default)]
+pub struct Contract {
+    pub functions: Vec<Function>,
+    pub events: Vec<Event>,
+}
+
+impl Contract {
+    pub fn from_json(json: &str) -> io::Result<Self> {
+        Self::from_raw(serde_json::from_str(json)?)
+    }
+
+    pub fn from_slice(json: &[u8]) -> io::Result<Self> {
+        Self::from_raw(serde_json::from_slice(json)?)
+    }
+
+    fn from_raw(raw: RawAbi) -> io::Result<Self> {
+        let items = match raw {
+            RawAbi::Items(items) => items,
+            RawAbi::Artifact { abi } => abi,
+        };
+
+        let mut contract = Self::default();
+        for item in items {
+            let inputs = params(&item.inputs)?;
+            match item.kind.as_str() {
+                "function" => {
+                    let selector = keccak::funsel(&signature(&item.name, &inputs));
+                    contract.functions.push(Function {
+                        name: item.name,
+                        inputs,
+                        outputs: params(&item.outputs)?,
+                        selector,
+                    });
+                }
+                "event" => {
+                    let topic = keccak::hash(signature(&item.name, &inputs).as_bytes());
+                    contract.events.push(Event {
+                        name: item.name,
+                        inputs,
+                        anonymous: item.anonymous,
+                        topic: B256::new(topic),
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(contract)
+    }
+
+    /// Returns the first function called `name`; see [`Contract::functions_by_name`]
+    /// for overloads.
+    pub fn function(&self, name: &str) -> Option<&Function> {
+        self.functions.iter().find(|f| f.name == name)
+    }
+
+    pub fn functions_by_name<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Function> {
+        self.functions.iter().filter(move |f| f.name == name)
+    }
+
+    pub fn function_by_selector(&self, selector: u32) -> Option<&Function> {
+        self.functions.iter().find(|f| f.selector == selector)
+    }
+
+    pub fn event(&self, name: &str) -> Option<&Event> {
+        self.events.iter().find(|e| e.name == name)
+    }
+
+    pub fn event_by_topic(&self, topic: &B256) -> Option<&Event> {
+        self.events
+            .iter()
+            .find(|e| !e.anonymous && e.topic == *topic)
+    }
+
+    /// Finds the function matching the selector of `data` and decodes its
+    /// arguments.
+    pub fn decode_call(&self, data: &[u8]) -> Result<(&Function, Vec<AbiValue>), AbiError> {
+        let found = AbiDecoder::peek_funsel(data).ok_or(AbiError::Truncated {
+            offset: 0,
+            needed: 4,
+            available: data.len(),
+        })?;
+        let function = self
+            .function_by_selector(found)
+            .ok_or(AbiError::UnknownFunsel { found })?;
+        Ok((function, function.decode_input(data)?))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawAbi {
+    Items(Vec<RawItem>),
+    Artifact { abi: Vec<RawItem> },
+}
+
+#[derive(Deserialize)]
+struct RawItem {
+    #[serde(rename = "type", default = "default_item_kind")]
+    kind: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    inputs: Vec<RawParam>,
+    #[serde(default)]
+    outputs: Vec<RawParam>,
+    #[serde(default)]
+    anonymous: bool,
+}
+
+fn default_item_kind() -> String {
+    "function".to_string()
+}
+
+#[derive(Deserialize)]
+struct RawParam {
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type")]
+    ty: String,
+    #[serde(default)]
+    components: Vec<RawParam>,
+    #[serde(default)]
+    indexed: bool,
+}
+
+fn params(raw: &[RawParam]) -> io::Result<Vec<Param>> {
+    raw.iter()
+        .map(|p| {
+            Ok(Param {
+                name: p.name.clone(),
+                kind: ParamType::parse(&p.ty, &p.components)?,
+                indexed: p.indexed,
+            })
+        })
+        .collect()
+}
+
+fn signature(name: &str, inputs: &[Param]) -> String {
+    let kinds: Vec<ParamType> = inputs.iter().map(|p| p.kind.clone()).collect();
+    format!("{name}{}", ParamType::Tuple(kinds))
+}
+
+fn mismatch(expected: &ParamType, found: &AbiValue) -> AbiError {
+    AbiError::TypeMismatch {
+        expected: expected.to_string(),
+        found: found.kind().to_string(),
+    }
+}
+
+fn encode_params(
+    enc: &mut AbiEncoder,
+    params: &[Param],
+    values: &[AbiValue],
+) -> Result<(), AbiError> {
+    if params.len() != values.len() {
+        return Err(AbiError::TypeMismatch {
+            expected: format!("{} values", params.len()),
+            found: format!("{} values", values.len()),
+        });
+    }
+    for (param, value) in params.iter().zip(values) {
+        encode_value(enc, &param.kind, value)?;
+    }
+    Ok(())
+}
+
+/// Encodes `values` as the members of a tuple of `types`.
+fn encode_members<'t>(
+    enc: &mut AbiEncoder,
+    mut types: impl Iterator<Item = &'t ParamType>,
+    values: &[AbiValue],
+) -> Result<(), AbiError> {
+    for value in values {
+        let ty = types.next().expect("caller checked the member count");
+        encode_value(enc, ty, value)?;
+    }
+    Ok(())
+}
+
+fn encode_value(enc: &mut AbiEncoder, ty: &ParamType, value: &AbiValue) -> Result<(), AbiError> {
+    match (ty, value) {
+        (ParamType::Address, AbiValue::Address(address)) => {
+            enc.put_address(address);
+        }
+        (ParamType::Bool, AbiValue::Bool(value)) => {
+            enc.put_bool(*value);
+        }
+        (ParamType::Uint(bits), AbiValue::Uint(value)) => {
+            if !fits_uint(&value.0, *bits) {
+                return Err(mismatch(ty, &AbiValue::Uint(*value)));
+            }
+            enc.put_uint256(value);
+        }
+        (ParamType::Int(bits), AbiValue::Int(value)) => {
+            if !fits_int(&value.0, *bits) {
+                return Err(mismatch(ty, &AbiValue::Int(*value)));
+            }
+            enc.put_int256(value);
+        }
+        (ParamType::FixedBytes(n), AbiValue::FixedBytes(data)) if data.len() == *n => {
+            let mut word = [0u8; WORD];
+            word[..*n].copy_from_slice(data);
+            enc.put_word(word);
+        }
+        (ParamType::Bytes, AbiValue::Bytes(data)) => {
+            enc.put_bytes(data);
+        }
+        (ParamType::String, AbiValue::String(value)) => {
+            enc.put_string(value);
+        }
+        (ParamType::Array(inner), AbiValue::Array(values)) => {
+            let mut sub = AbiEncoder::with_prefix(U256::from(values.len()).0.to_vec());
+            encode_members(&mut sub, std::iter::repeat(&**inner), values)?;
+            enc.put_tail(sub.finish());
+        }
+        (ParamType::FixedArray(inner, len), AbiValue::FixedArray(values))
+            if values.len() == *len =>
+        {
+            encode_static_or_tail(enc, ty, std::iter::repeat(&**inner), values)?;
+        }
+        (ParamType::Tuple(members), AbiValue::Tuple(values)) if values.len() == members.len() => {
+            encode_static_or_tail(enc, ty, members.iter(), values)?;
+        }
+        _ => return Err(mismatch(ty, value)),
+    }
+    Ok(())
+}
+
+fn encode_static_or_tail<'t>(
+    enc: &mut AbiEncoder,
+    ty: &ParamType,
+    types: impl Iterator<Item = &'t ParamType>,
+    values: &[AbiValue],
+) -> Result<(), AbiError> {
+    if ty.is_dynamic() {
+        let mut sub = AbiEncoder::new();
+        encode_members(&mut sub, types, values)?;
+        enc.put_tail(sub.finish());
+        Ok(())
+    } else {
+        encode_members(enc, types, values)
+    }
+}
+
+fn decode_params(dec: &mut AbiDecoder<'_>, params: &[Param]) -> Result<Vec<AbiValue>, AbiError> {
+    params.iter().map(|p| decode_value(dec, &p.kind)).collect()
+}
+
+fn decode_value(dec: &mut AbiDecoder<'_>, ty: &ParamType) -> Result<AbiValue, AbiError> {
+    let offset = dec.offset();
+    Ok(match ty {
+        ParamType::Address => AbiValue::Address(dec.get_address()?),
+        ParamType::Bool => AbiValue::Bool(dec.get_bool()?),
+        ParamType::Uint(bits) => {
+            let word = dec.get_word()?;
+            if !fits_uint(word, *bits) {
+                return Err(AbiError::Overflow {
+                    offset,
+                    bits: *bits,
+                });
+            }
+            AbiValue::Uint(U256(*word))
+        }
+        ParamType::Int(bits) => {
+            let word = dec.get_word()?;
+            if !fits_int(word, *bits) {
+                return Err(AbiError::Overflow {
+                    offset,
+                    bits: *bits,
+                });
+            }
+            AbiValue::Int(I256(*word))
+        }
+        ParamType::FixedBytes(n) => {
+            let word = dec.get_word()?;
+            if word[*n..].iter().any(|&b| b != 0) {
+                return Err(AbiError::NonCanonical {
+                    offset,
+                    kind: "bytesN",
+                });
+            }
+            AbiValue::FixedBytes(word[..*n].to_vec())
+        }
+        ParamType::Bytes => AbiValue::Bytes(dec.get_bytes()?.to_vec()),
+        ParamType::String => AbiValue::String(dec.get_string()?.to_owned()),
+        ParamType::Array(inner) => {
+            let mut tail = dec.get_tail()?;
+            let len = tail.get_length()?;
+            let mut elements = tail.sub_frame();
+            let values = (0..len)
+                .map(|_| {
+                    elements.charge(1)?;
+                    decode_value(&mut elements, inner)
+                })
+                .collect::<Result<_, _>>()?;
+            AbiValue::Array(values)
+        }
+        ParamType::FixedArray(inner, len) => {
+            let mut tail;
+            let dec = if ty.is_dynamic() {
+                tail = dec.get_tail()?;
+                &mut tail
+            } else {
+                dec
+            };
+            let values = (0..*len)
+                .map(|_| {
+                    dec.charge(1)?;
+                    decode_value(dec, inner)
+                })
+                .collect::<Result<_, _>>()?;
+            AbiValue::FixedArray(values)
+        }
+        ParamType::Tuple(members) => {
+            let mut tail;
+            let dec = if ty.is_dynamic() {
+                tail = dec.get_tail()?;
+                &mut tail
+            } else {
+                dec
+            };
+            let values = members
+                .iter()
+                .map(|member| decode_value(dec, member))
+                .collect::<Result<_, _>>()?;
+            AbiValue::Tuple(values)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ERC20: &str = r#"[
+        {
+            "type": "function",
+            "name": "transfer",
+            "inputs": [
+                { "name": "to", "type": "address" },
+                { "name": "value", "type": "uint256" }
+            ],
+            "outputs": [{ "name": "", "type": "bool" }]
+        },
+        {
+            "type": "event",
+            "name": "Transfer",
+            "inputs": [
+                { "name": "from", "type": "address", "indexed": true },
+                { "name": "to", "type": "address", "indexed": true },
+                { "name": "value", "type": "uint256", "indexed": false }
+            ],
+            "anonymous": false
+        }
+    ]"#;
+
+    fn words(words: &[&str]) -> Vec<u8> {
+        hex::decode(words.concat()).unwrap()
+    }
+
+    fn single(ty: &str, components: &str) -> Function {
+        let json = format!(
+            r#"[{{ "name": "f", "inputs": [{{ "name": "x", "type": "{ty}", "components": {components} }}] }}]"#
+        );
+        Contract::from_json(&json).unwrap().functions.remove(0)
+    }
+
+    #[test]
+    fn loads_solc_abi_and_artifacts() {
+        let artifact = format!(r#"{{ "contractName": "Token", "abi": {ERC20} }}"#);
+        for json in [ERC20.to_string(), artifact] {
+            let contract = Contract::from_json(&json).unwrap();
+            let transfer = contract.function("transfer").unwrap();
+            assert_eq!(transfer.signature(), "transfer(address,uint256)");
+            assert_eq!(transfer.selector().to_le_bytes(), [0xa9, 0x05, 0x9c, 0xbb]);
+            let event = contract.event("Transfer").unwrap();
+            assert_eq!(event.signature(), "Transfer(address,address,uint256)");
+            assert_eq!(
+                hex::encode(event.topic().0),
+                "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+            );
+            assert!(contract.event_by_topic(&event.topic()).is_some());
+        }
+    }
+
+    #[test]
+    fn fixed_arrays_in_dynamic_arrays_round_trip() {
+        let f = single("uint8[2][]", "[]");
+        assert_eq!(f.signature(), "f(uint8[2][])");
+        let pair = |a: u64, b: u64| {
+            AbiValue::FixedArray(vec![AbiValue::Uint(a.into()), AbiValue::Uint(b.into())])
+        };
+        let args = vec![AbiValue::Array(vec![pair(1, 2), pair(3, 4)])];
+
+        let data = f.encode_input(&args).unwrap();
+        assert_eq!(f.decode_input(&data).unwrap(), args);
+    }
+
+    #[test]
+    fn tuple_arrays_round_trip() {
+        let f = single(
+            "tuple[]",
+            r#"[{ "name": "ok", "type": "bool" }, { "name": "note", "type": "string" }]"#,
+        );
+        assert_eq!(f.signature(), "f((bool,string)[])");
+        let entry = |ok: bool, note: &str| {
+            AbiValue::Tuple(vec![AbiValue::Bool(ok), AbiValue::String(note.to_string())])
+        };
+        let args = vec![AbiValue::Array(vec![entry(true, "a"), entry(false, "bc")])];
+
+        let data = f.encode_input(&args).unwrap();
+        assert_eq!(f.decode_input(&data).unwrap(), args);
+    }
+
+    #[test]
+    fn sized_integers_are_range_checked() {
+        let uint8 = single("uint8", "[]");
+        let too_big = [AbiValue::Uint(256u64.into())];
+        assert!(matches!(
+            uint8.encode_input(&too_big),
+            Err(AbiError::TypeMismatch { .. })
+        ));
+        let mut data = uint8.selector().to_le_bytes().to_vec();
+        data.extend(U256::from(256u64).0);
+        assert_eq!(
+            uint8.decode_input(&data),
+            Err(AbiError::Overflow { offset: 4, bits: 8 })
+        );
+
+        let int8 = single("int8", "[]");
+        let too_small = [AbiValue::Int((-129i64).into())];
+        assert!(matches!(
+            int8.encode_input(&too_small),
+            Err(AbiError::TypeMismatch { .. })
+        ));
+        let min = [AbiValue::Int((-128i64).into())];
+        let data = int8.encode_input(&min).unwrap();
+        assert_eq!(int8.decode_input(&data).unwrap(), min);
+        let mut data = int8.selector().to_le_bytes().to_vec();
+        data.extend(U256::from(0x80u64).0);
+        assert_eq!(
+            int8.decode_input(&data),
+            Err(AbiError::Overflow { offset: 4, bits: 8 })
+        );
+    }
+
+    #[test]
+    fn decode_call_rejects_unknown_selectors() {
+        let contract = Contract::from_json(ERC20).unwrap();
+        let transfer = contract.function("transfer").unwrap();
+        let args = [
+            AbiValue::Address(Address::new([0x11; 20])),
+            AbiValue::Uint(5u64.into()),
+        ];
+        let data = transfer.encode_input(&args).unwrap();
+        let (function, values) = contract.decode_call(&data).unwrap();
+        assert_eq!(function.name, "transfer");
+        assert_eq!(values, args);
+
+        let data = hex::decode("deadbeef").unwrap();
+        assert_eq!(
+            contract.decode_call(&data).err(),
+            Some(AbiError::UnknownFunsel {
+                found: u32::from_le_bytes([0xde, 0xad, 0xbe, 0xef]),
+            })
+        );
+    }
+
+    #[test]
+    fn aliased_nested_arrays_are_rejected() {
+        let contract = Contract::from_json(
+            r#"[{ "type": "function", "name": "f", "inputs": [{ "name": "xs", "type": "uint256[][]" }] }]"#,
+        )
+        .unwrap();
+        let f = contract.function("f").unwrap();
+        let mut data = f.selector().to_le_bytes().to_vec();
+        data.extend(words(&[
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000004",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+            "0000000000000000000000000000000000000000000000000000000000000004",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000000000000000000000000000000000000000000003",
+            "0000000000000000000000000000000000000000000000000000000000000004",
+        ]));
+
+        assert!(matches!(
+            f.decode_input(&data),
+            Err(AbiError::Oversized { .. })
+        ));
+    }
+}
@@ -0,0 +1,139 @@
+//! Non-standard packed ABI encoding, as produced by `abi.encodePacked`.
+//!
+//! Values are concatenated without padding: addresses take 20 bytes,
+//! `uintN` and `bytesN` take `N / 8` and `N` bytes respectively, and dynamic
+//! `bytes` are written raw, without a length, so only the last value of a
+//! packed payload may be dynamic.
+
+use super::error::AbiError;
+use super::types::{Address, FixedBytes, U256};
+
+#[derive(Debug, Clone, Default)]
+pub struct PackedEncoder {
+    out: Vec<u8>,
+}
+
+impl PackedEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn put_address(&mut self, value: &Address) -> &mut Self {
+        self.put_bytes(&value.0)
+    }
+
+    pub fn put_uint256(&mut self, value: &U256) -> &mut Self {
+        self.put_bytes(&value.0)
+    }
+
+    /// Writes the low `bits / 8` bytes of `value`, failing if it does not fit.
+    pub fn put_uint(&mut self, bits: usize, value: &U256) -> Result<&mut Self, AbiError> {
+        let unused = 32 - uint_len(bits)?;
+        if value.0[..unused].iter().any(|&b| b != 0) {
+            return Err(AbiError::Overflow {
+                offset: self.out.len(),
+                bits,
+            });
+        }
+        Ok(self.put_bytes(&value.0[unused..]))
+    }
+
+    pub fn put_bool(&mut self, value: bool) -> &mut Self {
+        self.put_bytes(&[u8::from(value)])
+    }
+
+    pub fn put_fixed_bytes<const N: usize>(&mut self, value: &FixedBytes<N>) -> &mut Self {
+        self.put_bytes(&value.0)
+    }
+
+    /// Appends raw bytes, e.g. the trailing `bytes` of a portal payload or a
+    /// nested standard ABI encoding.
+    pub fn put_bytes(&mut self, value: &[u8]) -> &mut Self {
+        self.out.extend_from_slice(value);
+        self
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.out
+    }
+}
+
+/// Reads tightly packed values from a borrowed payload.
+#[derive(Debug, Clone)]
+pub struct PackedDecoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PackedDecoder<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    pub fn get_address(&mut self) -> Result<Address, AbiError> {
+        self.get_array().map(Address)
+    }
+
+    pub fn get_uint256(&mut self) -> Result<U256, AbiError> {
+        self.get_array().map(U256)
+    }
+
+    /// Reads a `uintN` of `bits / 8` bytes.
+    pub fn get_uint(&mut self, bits: usize) -> Result<U256, AbiError> {
+        let data = self.get_bytes(uint_len(bits)?)?;
+        Ok(U256::from_be_slice(data).expect("uintN is at most 32 bytes"))
+    }
+
+    pub fn get_bool(&mut self) -> Result<bool, AbiError> {
+        let offset = self.pos;
+        match self.get_bytes(1)?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(AbiError::NonCanonical {
+                offset,
+                kind: "bool",
+            }),
+        }
+    }
+
+    pub fn get_fixed_bytes<const N: usize>(&mut self) -> Result<FixedBytes<N>, AbiError> {
+        self.get_array().map(FixedBytes)
+    }
+
+    /// Reads the next `n` bytes.
+    pub fn get_bytes(&mut self, n: usize) -> Result<&'a [u8], AbiError> {
+        let available = self.data.len() - self.pos;
+        if n > available {
+            return Err(AbiError::Truncated {
+                offset: self.pos,
+                needed: n,
+                available,
+            });
+        }
+        let data = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(data)
+    }
+
+    /// Consumes and returns everything left, the trailing dynamic value.
+    pub fn rest(&mut self) -> &'a [u8] {
+        let data = &self.data[self.pos..];
+        self.pos = self.data.len();
+        data
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn get_array<const N: usize>(&mut self) -> Result<[u8; N], AbiError> {
+        self.get_bytes(N).map(|data| data.try_into().unwrap())
+    }
+}
+
+fn uint_len(bits: usize) -> Result<usize, AbiError> {
+    if !(8..=256).contains(&bits) || !bits.is_multiple_of(8) {
+        return Err(AbiError::InvalidWidth { bits });
+    }
+    Ok(bits / 8)
+}
@@ -0,0 +1,247 @@
+//! Fixed-size Solidity value types shared by the safe ABI encoder and decoder.
+
+use std::{fmt, io, str::FromStr};
+
+use crate::{cmt_abi_address_t, cmt_abi_u256_t};
+
+fn parse_hex_exact<const N: usize>(s: &str, what: &str) -> io::Result<[u8; N]> {
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    if digits.len() != N * 2 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{what} must be {} hex chars ({N} bytes), got {}",
+                N * 2,
+                digits.len()
+            ),
+        ));
+    }
+    let mut out = [0u8; N];
+    hex::decode_to_slice(digits, &mut out)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("invalid hex: {e}")))?;
+    Ok(out)
+}
+
+/// A 20-byte Ethereum address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Address(pub [u8; 20]);
+
+impl Address {
+    pub const ZERO: Self = Self([0u8; 20]);
+
+    pub const fn new(bytes: [u8; 20]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        bytes.try_into().ok().map(Self)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+}
+
+impl FromStr for Address {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        parse_hex_exact(s, "address").map(Self)
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+impl From<[u8; 20]> for Address {
+    fn from(bytes: [u8; 20]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<cmt_abi_address_t> for Address {
+    fn from(address: cmt_abi_address_t) -> Self {
+        Self(address.data)
+    }
+}
+
+impl From<Address> for cmt_abi_address_t {
+    fn from(address: Address) -> Self {
+        cmt_abi_address_t { data: address.0 }
+    }
+}
+
+/// A 256-bit unsigned integer stored as a big-endian word, exactly as it
+/// appears in ABI encoded data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct U256(pub [u8; 32]);
+
+impl U256 {
+    pub const ZERO: Self = Self([0u8; 32]);
+    pub const MAX: Self = Self([0xff; 32]);
+
+    pub const fn from_be_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    pub const fn to_be_bytes(self) -> [u8; 32] {
+        self.0
+    }
+
+    /// Builds a value from up to 32 big-endian bytes, left padding with zeros.
+    pub fn from_be_slice(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() > 32 {
+            return None;
+        }
+        let mut data = [0u8; 32];
+        data[32 - bytes.len()..].copy_from_slice(bytes);
+        Some(Self(data))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0u8; 32]
+    }
+}
+
+impl FromStr for U256 {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        let digits = s.strip_prefix("0x").unwrap_or(s);
+        if digits.len() > 64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "u256 hex too long (>32 bytes)",
+            ));
+        }
+        parse_hex_exact::<32>(&format!("{digits:0>64}"), "u256").map(Self)
+    }
+}
+
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+macro_rules! u256_from_uint {
+    ($($t:ty),*) => {$(
+        impl From<$t> for U256 {
+            fn from(value: $t) -> Self {
+                let be = value.to_be_bytes();
+                let mut data = [0u8; 32];
+                data[32 - be.len()..].copy_from_slice(&be);
+                Self(data)
+            }
+        }
+    )*};
+}
+
+u256_from_uint!(u8, u16, u32, u64, u128, usize);
+
+impl From<cmt_abi_u256_t> for U256 {
+    fn from(value: cmt_abi_u256_t) -> Self {
+        Self(value.data)
+    }
+}
+
+impl From<U256> for cmt_abi_u256_t {
+    fn from(value: U256) -> Self {
+        cmt_abi_u256_t { data: value.0 }
+    }
+}
+
+/// A 256-bit signed integer stored as a big-endian two's complement word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct I256(pub [u8; 32]);
+
+impl I256 {
+    pub const ZERO: Self = Self([0u8; 32]);
+    pub const MINUS_ONE: Self = Self([0xff; 32]);
+
+    pub const fn from_be_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    pub const fn to_be_bytes(self) -> [u8; 32] {
+        self.0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.0[0] & 0x80 != 0
+    }
+}
+
+impl fmt::Display for I256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+macro_rules! i256_from_int {
+    ($($t:ty),*) => {$(
+        impl From<$t> for I256 {
+            fn from(value: $t) -> Self {
+                let be = value.to_be_bytes();
+                let fill = if value < 0 { 0xff } else { 0x00 };
+                let mut data = [fill; 32];
+                data[32 - be.len()..].copy_from_slice(&be);
+                Self(data)
+            }
+        }
+    )*};
+}
+
+i256_from_int!(i8, i16, i32, i64, i128, isize);
+
+/// A Solidity `bytesN` value, `1 <= N <= 32`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FixedBytes<const N: usize>(pub [u8; N]);
+
+/// A 32-byte word, e.g. a keccak hash or a `bytes32` value.
+pub type B256 = FixedBytes<32>;
+
+impl<const N: usize> FixedBytes<N> {
+    pub const ZERO: Self = Self([0u8; N]);
+
+    pub const fn new(bytes: [u8; N]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        bytes.try_into().ok().map(Self)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; N] {
+        &self.0
+    }
+}
+
+impl<const N: usize> Default for FixedBytes<N> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for FixedBytes<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl<const N: usize> FromStr for FixedBytes<N> {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        parse_hex_exact(s, "fixed bytes").map(Self)
+    }
+}
+
+impl<const N: usize> fmt::Display for FixedBytes<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
@@ -0,0 +1,118 @@
+//! Native unsigned integer helpers for `uintN` words.
+
+use super::codec::{AbiDecode, AbiEncode};
+use super::decoder::{AbiDecoder, fits_uint};
+use super::encoder::AbiEncoder;
+use super::error::AbiError;
+use super::types::U256;
+
+/// A native unsigned integer that maps to a Solidity `uintN`.
+pub trait UintLike: Copy + Into<U256> {
+    /// The Solidity width `N` of the type.
+    const BITS: usize;
+
+    /// Truncates a word to the type; callers check the range.
+    fn from_word(word: &[u8; 32]) -> Self;
+}
+
+macro_rules! impl_uint_like {
+    ($($t:ty),*) => {$(
+        impl UintLike for $t {
+            const BITS: usize = <$t>::BITS as usize;
+
+            fn from_word(word: &[u8; 32]) -> Self {
+                const SIZE: usize = size_of::<$t>();
+                <$t>::from_be_bytes(word[32 - SIZE..].try_into().unwrap())
+            }
+        }
+
+        impl AbiEncode for $t {
+            const DYNAMIC: bool = false;
+
+            fn encode(&self, enc: &mut AbiEncoder) {
+                enc.put_uint(*self);
+            }
+        }
+
+        impl<'a> AbiDecode<'a> for $t {
+            const DYNAMIC: bool = false;
+
+            fn decode(dec: &mut AbiDecoder<'a>) -> Result<Self, AbiError> {
+                dec.get_uint()
+            }
+        }
+    )*};
+}
+
+impl_uint_like!(u8, u16, u32, u64, u128);
+
+impl UintLike for U256 {
+    const BITS: usize = 256;
+
+    fn from_word(word: &[u8; 32]) -> Self {
+        U256(*word)
+    }
+}
+
+/// Encodes `value` as a big-endian, zero padded 32-byte word.
+pub fn encode_uint<T: UintLike>(value: T) -> [u8; 32] {
+    value.into().0
+}
+
+/// Decodes a word into `T`, failing if the value does not fit.
+pub fn decode_uint<T: UintLike>(word: &[u8; 32]) -> Result<T, AbiError> {
+    if !fits_uint(word, T::BITS) {
+        return Err(AbiError::Overflow {
+            offset: 0,
+            bits: T::BITS,
+        });
+    }
+    Ok(T::from_word(word))
+}
+
+impl AbiDecoder<'_> {
+    /// Decodes a `uintN` into the native type `T`.
+    pub fn get_uint<T: UintLike>(&mut self) -> Result<T, AbiError> {
+        let offset = self.offset();
+        let word = self.get_word()?;
+        decode_uint(word).map_err(|_| AbiError::Overflow {
+            offset,
+            bits: T::BITS,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(hex: &str) -> [u8; 32] {
+        hex::decode(hex).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn u8_overflow() {
+        let max = word("00000000000000000000000000000000000000000000000000000000000000ff");
+        assert_eq!(decode_uint::<u8>(&max), Ok(u8::MAX));
+        assert_eq!(encode_uint(u8::MAX), max);
+        let too_big = word("0000000000000000000000000000000000000000000000000000000000000100");
+        assert_eq!(
+            decode_uint::<u8>(&too_big),
+            Err(AbiError::Overflow { offset: 0, bits: 8 })
+        );
+    }
+
+    #[test]
+    fn u128_overflow() {
+        let max = word("00000000000000000000000000000000ffffffffffffffffffffffffffffffff");
+        assert_eq!(decode_uint::<u128>(&max), Ok(u128::MAX));
+        let too_big = word("ffffffffffffffffffffffffffffffff00000000000000000000000000000000");
+        assert_eq!(
+            decode_uint::<u128>(&too_big),
+            Err(AbiError::Overflow {
+                offset: 0,
+                bits: 128,
+            })
+        );
+    }
+}
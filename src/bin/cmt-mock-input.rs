@@ -0,0 +1,492 @@
+//! Writes input files for the libcmt host mock driver.
+//!
+//! Each file holds one input as `cmt_rollup_read_advance_state` or
+//! `cmt_rollup_read_inspect_state` reads it; list them in `CMT_INPUTS`
+//! (`0:<file>` for advance, `1:<file>` for inspect) to replay them.
+//!
+//! ```text
+//! cmt-mock-input --sender 0x... --payload-text hello -o input-0.bin
+//! cmt-mock-input --deposit erc20 --token 0x... --depositor 0x... --value 100 -o input-1.bin
+//! cmt-mock-input --json scenario.json
+//! ```
+//!
+//! The JSON file holds one input object or an array of them, with the same
+//! fields as the flags in snake case plus `output`; numbers are JSON
+//! numbers or strings. Flags override the fields of every input read from
+//! JSON.
+
+use std::{env, fs, io, process};
+
+use libcmt_binding_rust::abi::{Address, U256};
+use libcmt_binding_rust::portals::{
+    Deposit, Erc20Deposit, Erc721Deposit, Erc1155BatchDeposit, Erc1155SingleDeposit, EtherDeposit,
+};
+use libcmt_binding_rust::rollup::{Advance, Metadata};
+use serde::Deserialize;
+
+const USAGE: &str = "\
+usage: cmt-mock-input [--json FILE] [OPTIONS] [-o FILE]
+
+input:
+  --json FILE             read one input or an array of inputs (`-` for stdin)
+  -o, --output FILE       file to write
+  --inspect               write an inspect request holding only the payload
+
+advance metadata:
+  --chain-id N            (default 1)
+  --app-contract ADDR     (default zero address)
+  --sender ADDR           msg.sender (default zero address; portal for deposits)
+  --block-number N        (default 0)
+  --block-timestamp N     (default 0)
+  --prev-randao HEX       (default 0)
+  --index N               (default 0)
+
+payload:
+  --payload HEX           raw payload bytes
+  --payload-text TEXT     UTF-8 payload
+  --deposit KIND          portal deposit: ether, erc20, erc721, erc1155-single,
+                          erc1155-batch, built from:
+    --token ADDR  --depositor ADDR  --value N  --token-id N
+    --token-ids N,N,..  --values N,N,..
+    --base-layer-data HEX  --exec-layer-data HEX
+
+numbers are decimal, or hex with a 0x prefix";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Spec {
+    output: Option<String>,
+    inspect: bool,
+    chain_id: Option<Number>,
+    app_contract: Option<String>,
+    sender: Option<String>,
+    block_number: Option<Number>,
+    block_timestamp: Option<Number>,
+    prev_randao: Option<Number>,
+    index: Option<Number>,
+    payload: Option<String>,
+    payload_text: Option<String>,
+    deposit: Option<DepositSpec>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct DepositSpec {
+    kind: String,
+    token: Option<String>,
+    depositor: Option<String>,
+    value: Option<Number>,
+    token_id: Option<Number>,
+    token_ids: Vec<Number>,
+    values: Vec<Number>,
+    base_layer_data: Option<String>,
+    exec_layer_data: Option<String>,
+}
+
+/// A number given as a flag or in JSON: a JSON number, or a string in
+/// decimal or hex with a `0x` prefix.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(
+    untagged,
+    expecting = "a number, or a string in decimal or 0x-prefixed hex"
+)]
+enum Number {
+    Json(u64),
+    Text(String),
+}
+
+impl Number {
+    fn to_u64(&self) -> io::Result<u64> {
+        match self {
+            Self::Json(n) => Ok(*n),
+            Self::Text(s) => parse_u64(s),
+        }
+    }
+
+    fn to_u256(&self) -> io::Result<U256> {
+        match self {
+            Self::Json(n) => Ok(U256::from(*n)),
+            Self::Text(s) => parse_u256(s),
+        }
+    }
+}
+
+impl Spec {
+    /// Fields set in `other` replace those in `self`.
+    fn merge(&mut self, other: &Spec) {
+        fn set<T: Clone>(dst: &mut Option<T>, src: &Option<T>) {
+            if src.is_some() {
+                dst.clone_from(src);
+            }
+        }
+        set(&mut self.output, &other.output);
+        self.inspect |= other.inspect;
+        set(&mut self.chain_id, &other.chain_id);
+        set(&mut self.app_contract, &other.app_contract);
+        set(&mut self.sender, &other.sender);
+        set(&mut self.block_number, &other.block_number);
+        set(&mut self.block_timestamp, &other.block_timestamp);
+        set(&mut self.prev_randao, &other.prev_randao);
+        set(&mut self.index, &other.index);
+        set(&mut self.payload, &other.payload);
+        set(&mut self.payload_text, &other.payload_text);
+        if let Some(src) = &other.deposit {
+            let dst = self.deposit.get_or_insert_with(DepositSpec::default);
+            if !src.kind.is_empty() {
+                dst.kind.clone_from(&src.kind);
+            }
+            set(&mut dst.token, &src.token);
+            set(&mut dst.depositor, &src.depositor);
+            set(&mut dst.value, &src.value);
+            set(&mut dst.token_id, &src.token_id);
+            if !src.token_ids.is_empty() {
+                dst.token_ids.clone_from(&src.token_ids);
+            }
+            if !src.values.is_empty() {
+                dst.values.clone_from(&src.values);
+            }
+            set(&mut dst.base_layer_data, &src.base_layer_data);
+            set(&mut dst.exec_layer_data, &src.exec_layer_data);
+        }
+    }
+
+    /// The bytes of the input file.
+    fn encode(&self) -> io::Result<Vec<u8>> {
+        let (sender, payload) = match &self.deposit {
+            Some(deposit) => {
+                let deposit = deposit.build()?;
+                (deposit.portal().address(), deposit.encode())
+            }
+            None => (parse_address(self.sender.as_deref())?, self.payload()?),
+        };
+        if self.inspect {
+            return Ok(payload);
+        }
+
+        let advance = Advance {
+            metadata: Metadata {
+                chain_id: u64_or(&self.chain_id, 1)?,
+                block_number: u64_or(&self.block_number, 0)?,
+                block_timestamp: u64_or(&self.block_timestamp, 0)?,
+                index: u64_or(&self.index, 0)?,
+            },
+            app_contract: parse_address(self.app_contract.as_deref())?.to_string(),
+            msg_sender: sender.to_string(),
+            prev_randao: u256_or_zero(&self.prev_randao)?.to_string(),
+            payload: format!("0x{}", hex::encode(payload)),
+        };
+        advance.encode_input().map_err(io::Error::from)
+    }
+
+    fn payload(&self) -> io::Result<Vec<u8>> {
+        match (&self.payload, &self.payload_text) {
+            (Some(_), Some(_)) => Err(invalid("use only one of --payload and --payload-text")),
+            (Some(hex), None) => parse_hex(hex),
+            (None, Some(text)) => Ok(text.as_bytes().to_vec()),
+            (None, None) => Ok(Vec::new()),
+        }
+    }
+}
+
+impl DepositSpec {
+    fn build(&self) -> io::Result<Deposit> {
+        let sender = parse_address(self.depositor.as_deref())?;
+        let token = || parse_address(self.token.as_deref());
+        let value = || u256_or_zero(&self.value);
+        let token_id = || u256_or_zero(&self.token_id);
+        let base_layer_data = parse_hex(self.base_layer_data.as_deref().unwrap_or(""))?;
+        let exec_layer_data = parse_hex(self.exec_layer_data.as_deref().unwrap_or(""))?;
+
+        Ok(match self.kind.as_str() {
+            "ether" => Deposit::Ether(EtherDeposit {
+                sender,
+                value: value()?,
+                exec_layer_data,
+            }),
+            "erc20" => Deposit::Erc20(Erc20Deposit {
+                token: token()?,
+                sender,
+                value: value()?,
+                exec_layer_data,
+            }),
+            "erc721" => Deposit::Erc721(Erc721Deposit {
+                token: token()?,
+                sender,
+                token_id: token_id()?,
+                base_layer_data,
+                exec_layer_data,
+            }),
+            "erc1155-single" => Deposit::Erc1155Single(Erc1155SingleDeposit {
+                token: token()?,
+                sender,
+                token_id: token_id()?,
+                value: value()?,
+                base_layer_data,
+                exec_layer_data,
+            }),
+            "erc1155-batch" => {
+                let token_ids = parse_u256_list(&self.token_ids)?;
+                let values = parse_u256_list(&self.values)?;
+                if token_ids.len() != values.len() {
+                    return Err(invalid("--token-ids and --values differ in length"));
+                }
+                Deposit::Erc1155Batch(Erc1155BatchDeposit {
+                    token: token()?,
+                    sender,
+                    token_ids,
+                    values,
+                    base_layer_data,
+                    exec_layer_data,
+                })
+            }
+            kind => return Err(invalid(format!("unknown deposit kind `{kind}`"))),
+        })
+    }
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message.into())
+}
+
+fn parse_address(s: Option<&str>) -> io::Result<Address> {
+    s.map_or(Ok(Address::ZERO), str::parse)
+}
+
+fn parse_hex(s: &str) -> io::Result<Vec<u8>> {
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    hex::decode(digits).map_err(|e| invalid(format!("invalid hex `{s}`: {e}")))
+}
+
+/// Decimal up to `u128`, or hex with a `0x` prefix.
+fn parse_u256(s: &str) -> io::Result<U256> {
+    if s.starts_with("0x") {
+        return s.parse();
+    }
+    s.parse::<u128>()
+        .map(U256::from)
+        .map_err(|e| invalid(format!("invalid number `{s}`: {e}")))
+}
+
+fn parse_u256_list(items: &[Number]) -> io::Result<Vec<U256>> {
+    items.iter().map(Number::to_u256).collect()
+}
+
+fn u256_or_zero(n: &Option<Number>) -> io::Result<U256> {
+    n.as_ref().map_or(Ok(U256::ZERO), Number::to_u256)
+}
+
+fn parse_u64(s: &str) -> io::Result<u64> {
+    let parsed = match s.strip_prefix("0x") {
+        Some(digits) => u64::from_str_radix(digits, 16),
+        None => s.parse(),
+    };
+    parsed.map_err(|e| invalid(format!("invalid number `{s}`: {e}")))
+}
+
+fn u64_or(n: &Option<Number>, default: u64) -> io::Result<u64> {
+    n.as_ref().map_or(Ok(default), Number::to_u64)
+}
+
+/// Parses the command line into the JSON file to read, if any, and the
+/// fields given as flags.
+fn parse_args(args: &[String]) -> io::Result<(Option<String>, Spec)> {
+    let mut json = None;
+    let mut spec = Spec::default();
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| invalid(format!("{flag} needs a value")))
+        };
+        match flag.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                process::exit(0);
+            }
+            "--json" => json = Some(value()?),
+            "-o" | "--output" => spec.output = Some(value()?),
+            "--inspect" => spec.inspect = true,
+            "--chain-id" => spec.chain_id = Some(Number::Text(value()?)),
+            "--app-contract" => spec.app_contract = Some(value()?),
+            "--sender" => spec.sender = Some(value()?),
+            "--block-number" => spec.block_number = Some(Number::Text(value()?)),
+            "--block-timestamp" => spec.block_timestamp = Some(Number::Text(value()?)),
+            "--prev-randao" => spec.prev_randao = Some(Number::Text(value()?)),
+            "--index" => spec.index = Some(Number::Text(value()?)),
+            "--payload" => spec.payload = Some(value()?),
+            "--payload-text" => spec.payload_text = Some(value()?),
+            _ => {
+                let deposit = spec.deposit.get_or_insert_with(DepositSpec::default);
+                match flag.as_str() {
+                    "--deposit" => deposit.kind = value()?,
+                    "--token" => deposit.token = Some(value()?),
+                    "--depositor" => deposit.depositor = Some(value()?),
+                    "--value" => deposit.value = Some(Number::Text(value()?)),
+                    "--token-id" => deposit.token_id = Some(Number::Text(value()?)),
+                    "--token-ids" => deposit.token_ids = split_list(&value()?),
+                    "--values" => deposit.values = split_list(&value()?),
+                    "--base-layer-data" => deposit.base_layer_data = Some(value()?),
+                    "--exec-layer-data" => deposit.exec_layer_data = Some(value()?),
+                    _ => return Err(invalid(format!("unknown flag `{flag}`\n\n{USAGE}"))),
+                }
+            }
+        }
+    }
+    if spec.deposit.as_ref().is_some_and(|d| d.kind.is_empty()) {
+        return Err(invalid("deposit flags need --deposit KIND"));
+    }
+    Ok((json, spec))
+}
+
+fn split_list(s: &str) -> Vec<Number> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| Number::Text(item.to_string()))
+        .collect()
+}
+
+fn read_specs(path: &str) -> io::Result<Vec<Spec>> {
+    let json = if path == "-" {
+        io::read_to_string(io::stdin())?
+    } else {
+        fs::read_to_string(path)?
+    };
+    let value: serde_json::Value = serde_json::from_str(&json)?;
+    if value.is_array() {
+        Ok(serde_json::from_value(value)?)
+    } else {
+        Ok(vec![serde_json::from_value(value)?])
+    }
+}
+
+fn run() -> io::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (json, flags) = parse_args(&args)?;
+    let mut specs = match json {
+        Some(path) => read_specs(&path)?,
+        None => vec![Spec::default()],
+    };
+
+    if specs.len() > 1 && flags.output.is_some() {
+        return Err(invalid("--output cannot be used with several JSON inputs"));
+    }
+
+    // Encode every input before writing any, so a bad one leaves no
+    // partial set of files behind.
+    let mut files = Vec::with_capacity(specs.len());
+    for (i, spec) in specs.iter_mut().enumerate() {
+        spec.merge(&flags);
+        let output = spec
+            .output
+            .clone()
+            .unwrap_or_else(|| format!("input-{i}.bin"));
+        files.push((output, spec.encode()?, spec.inspect));
+    }
+
+    let mut inputs = Vec::with_capacity(files.len());
+    for (output, data, inspect) in &files {
+        fs::write(output, data)?;
+        inputs.push(format!("{}:{output}", if *inspect { 1 } else { 0 }));
+    }
+    println!("CMT_INPUTS={}", inputs.join(","));
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("cmt-mock-input: {e}");
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libcmt_binding_rust::portals::Portal;
+
+    fn spec(json: &str) -> Spec {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn advance() {
+        let spec = spec(
+            r#"{
+                "chain_id": "0x1",
+                "sender": "0x1111111111111111111111111111111111111111",
+                "block_number": 2,
+                "block_timestamp": "3",
+                "index": 5,
+                "payload_text": "hello"
+            }"#,
+        );
+        let expected = Advance {
+            metadata: Metadata {
+                chain_id: 1,
+                block_number: 2,
+                block_timestamp: 3,
+                index: 5,
+            },
+            app_contract: Address::ZERO.to_string(),
+            msg_sender: Address::new([0x11; 20]).to_string(),
+            prev_randao: U256::ZERO.to_string(),
+            payload: format!("0x{}", hex::encode(b"hello")),
+        };
+        assert_eq!(spec.encode().unwrap(), expected.encode_input().unwrap());
+    }
+
+    #[test]
+    fn inspect_is_the_bare_payload() {
+        let spec = spec(r#"{ "inspect": true, "payload": "0xdeadbeef", "index": 7 }"#);
+        assert_eq!(spec.encode().unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+    }
+
+    #[test]
+    fn erc20_deposit() {
+        let spec = spec(
+            r#"{
+                "deposit": {
+                    "kind": "erc20",
+                    "token": "0x2222222222222222222222222222222222222222",
+                    "depositor": "0x3333333333333333333333333333333333333333",
+                    "value": 100,
+                    "exec_layer_data": "0xabcd"
+                }
+            }"#,
+        );
+        let deposit = Erc20Deposit {
+            token: Address::new([0x22; 20]),
+            sender: Address::new([0x33; 20]),
+            value: U256::from(100u64),
+            exec_layer_data: vec![0xab, 0xcd],
+        };
+
+        let advance = Advance::decode_input(&spec.encode().unwrap()).unwrap();
+        assert_eq!(advance.msg_sender, Portal::Erc20.address().to_string());
+        assert_eq!(
+            advance.payload,
+            format!("0x{}", hex::encode(deposit.encode()))
+        );
+    }
+
+    #[test]
+    fn numbers_accept_json_numbers_and_strings() {
+        let spec = spec(
+            r#"{ "chain_id": 10, "block_number": "0x10", "deposit": { "value": "0x64", "token_ids": [1, "2"] } }"#,
+        );
+        assert_eq!(spec.chain_id.unwrap().to_u64().unwrap(), 10);
+        assert_eq!(spec.block_number.unwrap().to_u64().unwrap(), 16);
+        let deposit = spec.deposit.unwrap();
+        assert_eq!(
+            deposit.value.unwrap().to_u256().unwrap(),
+            U256::from(100u64)
+        );
+        assert_eq!(
+            parse_u256_list(&deposit.token_ids).unwrap(),
+            [U256::from(1u64), U256::from(2u64)]
+        );
+        assert!(serde_json::from_str::<Spec>(r#"{ "index": -1 }"#).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+//! Structured errors for the `Rollup` and `IoDriver` wrappers.
+//!
+//! libcmt reports failures as negative errno values. [`CmtError`] keeps the
+//! name of the failing call and, where the wrapper knows them, the sizes
+//! involved, and converts into [`io::Error`] for callers that only deal in
+//! `io::Result`.
+
+use std::{error, fmt, io};
+
+#[derive(Debug)]
+pub enum CmtError {
+    /// The data does not fit the buffer libcmt writes it to. Both sizes are
+    /// zero when the failing call does not report them.
+    BufferTooSmall {
+        op: &'static str,
+        requested: usize,
+        available: usize,
+    },
+    /// An output payload is larger than the TX buffer can hold once encoded.
+    PayloadTooLarge {
+        op: &'static str,
+        max: usize,
+        got: usize,
+    },
+    /// An argument was rejected before or by libcmt.
+    InvalidInput { op: &'static str, reason: String },
+    /// The host sent a request or response libcmt could not make sense of.
+    ProtocolViolation { op: &'static str, errno: i32 },
+    /// The host answered with a non-zero response code.
+    Rejected { op: &'static str, code: u16 },
+    /// Any other failure, with the original OS error.
+    Io { op: &'static str, source: io::Error },
+}
+
+impl CmtError {
+    pub(crate) fn invalid_input(op: &'static str, reason: impl fmt::Display) -> Self {
+        Self::InvalidInput {
+            op,
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Name of the libcmt call or wrapper method that failed.
+    pub fn op(&self) -> &'static str {
+        match self {
+            Self::BufferTooSmall { op, .. }
+            | Self::PayloadTooLarge { op, .. }
+            | Self::InvalidInput { op, .. }
+            | Self::ProtocolViolation { op, .. }
+            | Self::Rejected { op, .. }
+            | Self::Io { op, .. } => op,
+        }
+    }
+
+    /// The errno libcmt returned, if the error came from libcmt.
+    pub fn errno(&self) -> Option<i32> {
+        match self {
+            Self::BufferTooSmall { .. } => Some(libc::ENOBUFS),
+            Self::ProtocolViolation { errno, .. } => Some(*errno),
+            Self::Io { source, .. } => source.raw_os_error(),
+            Self::PayloadTooLarge { .. } | Self::InvalidInput { .. } | Self::Rejected { .. } => {
+                None
+            }
+        }
+    }
+}
+
+impl fmt::Display for CmtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BufferTooSmall {
+                op,
+                requested: 0,
+                available: 0,
+            } => write!(f, "{op}: buffer too small"),
+            Self::BufferTooSmall {
+                op,
+                requested,
+                available,
+            } => write!(
+                f,
+                "{op}: buffer too small, {requested} bytes requested, {available} available"
+            ),
+            Self::PayloadTooLarge { op, max, got } => write!(
+                f,
+                "{op}: payload of {got} bytes exceeds the maximum of {max} bytes"
+            ),
+            Self::InvalidInput { op, reason } => write!(f, "{op}: invalid input: {reason}"),
+            Self::ProtocolViolation { op, errno } => write!(
+                f,
+                "{op}: protocol violation: {}",
+                io::Error::from_raw_os_error(*errno)
+            ),
+            Self::Rejected { op, code } => write!(f, "{op}: rejected with response code {code}"),
+            Self::Io { op, source } => write!(f, "{op}: {source}"),
+        }
+    }
+}
+
+impl error::Error for CmtError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<CmtError> for io::Error {
+    fn from(e: CmtError) -> Self {
+        let kind = match &e {
+            CmtError::BufferTooSmall { .. }
+            | CmtError::PayloadTooLarge { .. }
+            | CmtError::InvalidInput { .. } => io::ErrorKind::InvalidInput,
+            CmtError::ProtocolViolation { .. } => io::ErrorKind::InvalidData,
+            CmtError::Rejected { .. } => io::ErrorKind::Other,
+            CmtError::Io { source, .. } => source.kind(),
+        };
+        io::Error::new(kind, e)
+    }
+}
+
+/// Maps a libcmt return code for a call that has no size information. As
+/// with the raw bindings, only zero is success.
+pub(crate) fn check(rc: i32, op: &'static str) -> Result<(), CmtError> {
+    if rc == 0 {
+        return Ok(());
+    }
+    Err(match -rc {
+        libc::EINVAL => CmtError::InvalidInput {
+            op,
+            reason: "rejected by libcmt".to_string(),
+        },
+        libc::ENOBUFS => CmtError::BufferTooSmall {
+            op,
+            requested: 0,
+            available: 0,
+        },
+        errno @ (libc::ENOSYS | libc::EBADMSG | libc::EPROTO | libc::ENODATA | libc::EDOM) => {
+            CmtError::ProtocolViolation { op, errno }
+        }
+        errno => CmtError::Io {
+            op,
+            source: io::Error::from_raw_os_error(errno),
+        },
+    })
+}
+
+/// Maps a libcmt return code for a call that writes `requested` bytes into
+/// a buffer of `available` bytes.
+pub(crate) fn check_buffer(
+    rc: i32,
+    op: &'static str,
+    requested: usize,
+    available: usize,
+) -> Result<(), CmtError> {
+    if rc == -libc::ENOBUFS {
+        return Err(CmtError::BufferTooSmall {
+            op,
+            requested,
+            available,
+        });
+    }
+    check(rc, op)
+}
@@ -0,0 +1,50 @@
+//! Typed Generic I/O (GIO) requests.
+//!
+//! A GIO request asks the host for data identified by a `domain` and an
+//! opaque `id`, e.g. the preimage of a hash. The host answers with a
+//! response code, zero on success, and the response data in the RX buffer.
+//! See [`crate::rollup::Rollup::gio`], and [`eth`] for typed lookups.
+
+pub mod eth;
+#[cfg(not(target_arch = "riscv64"))]
+pub mod mock;
+
+/// The domain of a GIO request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GioDomain {
+    /// The preimage of a Keccak-256 hash; the id is the 32-byte hash.
+    KeccakPreimage,
+    /// Any other domain understood by the host.
+    Custom(u16),
+}
+
+impl GioDomain {
+    pub const KECCAK_PREIMAGE: u16 = 0x2a;
+
+    pub fn raw(self) -> u16 {
+        match self {
+            Self::KeccakPreimage => Self::KECCAK_PREIMAGE,
+            Self::Custom(domain) => domain,
+        }
+    }
+
+    pub fn from_raw(domain: u16) -> Self {
+        match domain {
+            Self::KECCAK_PREIMAGE => Self::KeccakPreimage,
+            domain => Self::Custom(domain),
+        }
+    }
+}
+
+impl From<GioDomain> for u16 {
+    fn from(domain: GioDomain) -> Self {
+        domain.raw()
+    }
+}
+
+/// The host's answer to a GIO request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GioResponse {
+    pub code: u16,
+    pub data: Vec<u8>,
+}
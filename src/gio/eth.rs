@@ -0,0 +1,357 @@
+//! Preimage and Ethereum state lookups over GIO.
+//!
+//! Preimages live in the [`GioDomain::KeccakPreimage`] domain: the id is the
+//! 32-byte hash and the response is the raw preimage, which [`preimage`]
+//! checks against the hash.
+//!
+//! libcmt does not assign domains or wire formats to Ethereum state, so
+//! [`EthGio`] takes both from the caller: the domain of each lookup, and an
+//! [`EthCodec`] that builds the request ids and decodes the responses.
+//! [`AbiCodec`] is a plain ABI layout for hosts without a format of their
+//! own:
+//!
+//! | Lookup         | Id                                                     | Response |
+//! |----------------|--------------------------------------------------------|----------|
+//! | block header   | `abi.encode(uint64 number)`                            | `abi.encode(bytes32 hash, bytes32 parentHash, bytes32 stateRoot, uint64 number, uint64 timestamp)` |
+//! | account        | `abi.encode(bytes32 blockHash, address)`               | `abi.encode(uint64 nonce, uint256 balance, bytes32 codeHash, bytes32 storageRoot)` |
+//! | storage slot   | `abi.encode(bytes32 blockHash, address, bytes32 slot)` | `abi.encode(bytes32 value)` |
+
+use super::GioDomain;
+use crate::abi::{self, AbiError, Address, B256, U256};
+use crate::error::CmtError;
+use crate::keccak;
+use crate::rollup::Rollup;
+
+/// A lookup the host answers through GIO.
+pub trait EthQuery {
+    type Output;
+
+    /// The request id sent to the host.
+    fn id(&self) -> Vec<u8>;
+
+    /// Decodes the host's response data.
+    fn decode_response(&self, data: &[u8]) -> Result<Self::Output, CmtError>;
+}
+
+/// Sends `query` to the host in `domain` and decodes its response.
+pub fn query<Q: EthQuery>(
+    rollup: &mut Rollup,
+    domain: GioDomain,
+    query: &Q,
+) -> Result<Q::Output, CmtError> {
+    let response = rollup.gio(domain, &query.id())?;
+    query.decode_response(&response.data)
+}
+
+/// The preimage of `hash`, checked against it.
+pub fn preimage(rollup: &mut Rollup, hash: B256) -> Result<Vec<u8>, CmtError> {
+    query(rollup, GioDomain::KeccakPreimage, &PreimageQuery { hash })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreimageQuery {
+    pub hash: B256,
+}
+
+impl EthQuery for PreimageQuery {
+    type Output = Vec<u8>;
+
+    fn id(&self) -> Vec<u8> {
+        self.hash.0.to_vec()
+    }
+
+    fn decode_response(&self, data: &[u8]) -> Result<Vec<u8>, CmtError> {
+        if keccak::hash(data) != self.hash.0 {
+            return Err(bad_response("gio::eth::preimage"));
+        }
+        Ok(data.to_vec())
+    }
+}
+
+/// The request ids and response layouts a host uses for Ethereum state.
+pub trait EthCodec {
+    fn block_header_id(&self, number: u64) -> Vec<u8>;
+
+    fn decode_block_header(&self, data: &[u8]) -> Result<BlockHeader, AbiError>;
+
+    fn account_id(&self, block_hash: &B256, address: &Address) -> Vec<u8>;
+
+    fn decode_account(&self, data: &[u8]) -> Result<Account, AbiError>;
+
+    fn storage_id(&self, block_hash: &B256, address: &Address, slot: &B256) -> Vec<u8>;
+
+    fn decode_storage(&self, data: &[u8]) -> Result<B256, AbiError>;
+}
+
+/// The ABI layout in the module docs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AbiCodec;
+
+impl EthCodec for AbiCodec {
+    fn block_header_id(&self, number: u64) -> Vec<u8> {
+        abi::encode_params(&(number,))
+    }
+
+    fn decode_block_header(&self, data: &[u8]) -> Result<BlockHeader, AbiError> {
+        BlockHeader::decode(data)
+    }
+
+    fn account_id(&self, block_hash: &B256, address: &Address) -> Vec<u8> {
+        abi::encode_params(&(block_hash, address))
+    }
+
+    fn decode_account(&self, data: &[u8]) -> Result<Account, AbiError> {
+        Account::decode(data)
+    }
+
+    fn storage_id(&self, block_hash: &B256, address: &Address, slot: &B256) -> Vec<u8> {
+        abi::encode_params(&(block_hash, address, slot))
+    }
+
+    fn decode_storage(&self, data: &[u8]) -> Result<B256, AbiError> {
+        abi::decode_params::<(B256,)>(data).map(|(value,)| value)
+    }
+}
+
+/// Ethereum state lookups against a host that serves them in the given
+/// domains, encoded with `codec`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EthGio<C = AbiCodec> {
+    pub block_header_domain: GioDomain,
+    pub account_domain: GioDomain,
+    pub storage_domain: GioDomain,
+    pub codec: C,
+}
+
+impl EthGio<AbiCodec> {
+    /// Lookups in the given domains, using [`AbiCodec`].
+    pub fn new(
+        block_header_domain: GioDomain,
+        account_domain: GioDomain,
+        storage_domain: GioDomain,
+    ) -> Self {
+        Self::with_codec(
+            block_header_domain,
+            account_domain,
+            storage_domain,
+            AbiCodec,
+        )
+    }
+}
+
+impl<C: EthCodec> EthGio<C> {
+    pub fn with_codec(
+        block_header_domain: GioDomain,
+        account_domain: GioDomain,
+        storage_domain: GioDomain,
+        codec: C,
+    ) -> Self {
+        Self {
+            block_header_domain,
+            account_domain,
+            storage_domain,
+            codec,
+        }
+    }
+
+    pub fn block_header(&self, rollup: &mut Rollup, number: u64) -> Result<BlockHeader, CmtError> {
+        let id = self.codec.block_header_id(number);
+        let response = rollup.gio(self.block_header_domain, &id)?;
+        self.codec
+            .decode_block_header(&response.data)
+            .map_err(|_| bad_response("gio::eth::block_header"))
+    }
+
+    pub fn account(
+        &self,
+        rollup: &mut Rollup,
+        block_hash: &B256,
+        address: &Address,
+    ) -> Result<Account, CmtError> {
+        let id = self.codec.account_id(block_hash, address);
+        let response = rollup.gio(self.account_domain, &id)?;
+        self.codec
+            .decode_account(&response.data)
+            .map_err(|_| bad_response("gio::eth::account"))
+    }
+
+    pub fn storage_at(
+        &self,
+        rollup: &mut Rollup,
+        block_hash: &B256,
+        address: &Address,
+        slot: &B256,
+    ) -> Result<B256, CmtError> {
+        let id = self.codec.storage_id(block_hash, address, slot);
+        let response = rollup.gio(self.storage_domain, &id)?;
+        self.codec
+            .decode_storage(&response.data)
+            .map_err(|_| bad_response("gio::eth::storage_at"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub hash: B256,
+    pub parent_hash: B256,
+    pub state_root: B256,
+    pub number: u64,
+    pub timestamp: u64,
+}
+
+impl BlockHeader {
+    pub fn encode(&self) -> Vec<u8> {
+        abi::encode_params(&(
+            &self.hash,
+            &self.parent_hash,
+            &self.state_root,
+            self.number,
+            self.timestamp,
+        ))
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, AbiError> {
+        let (hash, parent_hash, state_root, number, timestamp) = abi::decode_params(data)?;
+        Ok(Self {
+            hash,
+            parent_hash,
+            state_root,
+            number,
+            timestamp,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Account {
+    pub nonce: u64,
+    pub balance: U256,
+    pub code_hash: B256,
+    pub storage_root: B256,
+}
+
+impl Account {
+    pub fn encode(&self) -> Vec<u8> {
+        abi::encode_params(&(
+            self.nonce,
+            &self.balance,
+            &self.code_hash,
+            &self.storage_root,
+        ))
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, AbiError> {
+        let (nonce, balance, code_hash, storage_root) = abi::decode_params(data)?;
+        Ok(Self {
+            nonce,
+            balance,
+            code_hash,
+            storage_root,
+        })
+    }
+}
+
+fn bad_response(op: &'static str) -> CmtError {
+    CmtError::ProtocolViolation {
+        op,
+        errno: libc::EBADMSG,
+    }
+}
+
+// The responders in `gio::mock` only exist on host builds.
+#[cfg(all(test, not(target_arch = "riscv64")))]
+mod tests {
+    use super::*;
+    use crate::gio::GioResponse;
+    use crate::gio::mock::{JsonResponder, PreimageStore};
+
+    const BLOCK_HEADER: GioDomain = GioDomain::Custom(0x100);
+    const ACCOUNT: GioDomain = GioDomain::Custom(0x101);
+    const STORAGE: GioDomain = GioDomain::Custom(0x102);
+
+    fn eth() -> EthGio {
+        EthGio::new(BLOCK_HEADER, ACCOUNT, STORAGE)
+    }
+
+    fn is_bad_response(e: &CmtError) -> bool {
+        matches!(
+            e,
+            CmtError::ProtocolViolation {
+                errno: libc::EBADMSG,
+                ..
+            }
+        )
+    }
+
+    #[test]
+    fn preimage_from_store() {
+        let mut store = PreimageStore::new();
+        let hash = B256::new(store.insert(b"hello".to_vec()));
+        let mut rollup = Rollup::new().unwrap();
+        rollup.set_gio_handler(store);
+
+        assert_eq!(preimage(&mut rollup, hash).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn preimage_hash_mismatch() {
+        let hash = B256::new(keccak::hash(b"hello"));
+        let mut responder = JsonResponder::default();
+        responder.insert(
+            GioDomain::KeccakPreimage,
+            hash.0.to_vec(),
+            GioResponse {
+                code: 0,
+                data: b"goodbye".to_vec(),
+            },
+        );
+        let mut rollup = Rollup::new().unwrap();
+        rollup.set_gio_handler(responder);
+
+        let e = preimage(&mut rollup, hash).unwrap_err();
+        assert!(is_bad_response(&e), "{e}");
+    }
+
+    #[test]
+    fn block_header_from_json() {
+        let header = BlockHeader {
+            hash: B256::new([1; 32]),
+            parent_hash: B256::new([2; 32]),
+            state_root: B256::new([3; 32]),
+            number: 9,
+            timestamp: 1_700_000_000,
+        };
+        let json = format!(
+            r#"[{{ "domain": {}, "id": "0x{}", "response": "0x{}" }}]"#,
+            BLOCK_HEADER.raw(),
+            hex::encode(AbiCodec.block_header_id(9)),
+            hex::encode(header.encode()),
+        );
+        let mut rollup = Rollup::new().unwrap();
+        rollup.set_gio_handler(JsonResponder::from_slice(json.as_bytes()).unwrap());
+
+        assert_eq!(eth().block_header(&mut rollup, 9).unwrap(), header);
+    }
+
+    #[test]
+    fn malformed_response() {
+        let block_hash = B256::new([4; 32]);
+        let address = Address::new([5; 20]);
+        let mut responder = JsonResponder::default();
+        responder.insert(
+            ACCOUNT,
+            AbiCodec.account_id(&block_hash, &address),
+            GioResponse {
+                code: 0,
+                data: vec![0; 31],
+            },
+        );
+        let mut rollup = Rollup::new().unwrap();
+        rollup.set_gio_handler(responder);
+
+        let e = eth()
+            .account(&mut rollup, &block_hash, &address)
+            .unwrap_err();
+        assert!(is_bad_response(&e), "{e}");
+    }
+}
@@ -0,0 +1,194 @@
+//! Stand-in GIO responders for running applications off the machine.
+//!
+//! On host builds a [`Rollup`](crate::rollup::Rollup) with a handler
+//! installed through `set_gio_handler` answers GIO requests from it instead
+//! of the libcmt mock, so GIO-dependent code can be tested offline.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::{fs, io};
+
+use serde::Deserialize;
+
+use super::{GioDomain, GioResponse};
+use crate::keccak;
+
+/// Answers GIO requests.
+pub trait GioHandler {
+    /// Returns the response to `id` in `domain`, or `None` if the handler
+    /// does not serve the request.
+    fn handle(&mut self, domain: GioDomain, id: &[u8]) -> Option<GioResponse>;
+}
+
+/// Handlers are tried in order; the first one that answers wins.
+impl GioHandler for Vec<Box<dyn GioHandler>> {
+    fn handle(&mut self, domain: GioDomain, id: &[u8]) -> Option<GioResponse> {
+        self.iter_mut()
+            .find_map(|handler| handler.handle(domain, id))
+    }
+}
+
+/// Serves [`GioDomain::KeccakPreimage`] requests from known preimages.
+#[derive(Debug, Clone, Default)]
+pub struct PreimageStore {
+    preimages: HashMap<[u8; 32], Vec<u8>>,
+}
+
+impl PreimageStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads every regular file in `dir` as a preimage. File names do not
+    /// matter; each file is keyed by the Keccak-256 hash of its contents.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        let mut store = Self::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                store.insert(fs::read(entry.path())?);
+            }
+        }
+        Ok(store)
+    }
+
+    /// Adds a preimage and returns its hash.
+    pub fn insert(&mut self, preimage: Vec<u8>) -> [u8; 32] {
+        let hash = keccak::hash(&preimage);
+        self.preimages.insert(hash, preimage);
+        hash
+    }
+
+    pub fn len(&self) -> usize {
+        self.preimages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.preimages.is_empty()
+    }
+}
+
+impl GioHandler for PreimageStore {
+    fn handle(&mut self, domain: GioDomain, id: &[u8]) -> Option<GioResponse> {
+        if domain != GioDomain::KeccakPreimage {
+            return None;
+        }
+        let hash: [u8; 32] = id.try_into().ok()?;
+        let data = self.preimages.get(&hash)?.clone();
+        Some(GioResponse { code: 0, data })
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonEntry {
+    domain: u16,
+    id: String,
+    #[serde(default)]
+    code: u16,
+    #[serde(default)]
+    response: String,
+}
+
+/// Serves fixed responses listed in a JSON file:
+///
+/// ```json
+/// [
+///   { "domain": 42, "id": "0x1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac8", "response": "0x68656c6c6f" },
+///   { "domain": 256, "id": "0x01", "code": 1 }
+/// ]
+/// ```
+///
+/// `code` defaults to zero and `response` to empty.
+#[derive(Debug, Clone, Default)]
+pub struct JsonResponder {
+    responses: HashMap<(u16, Vec<u8>), GioResponse>,
+}
+
+impl JsonResponder {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::from_slice(&fs::read(path)?)
+    }
+
+    pub fn from_slice(json: &[u8]) -> io::Result<Self> {
+        let entries: Vec<JsonEntry> = serde_json::from_slice(json)?;
+        let mut responder = Self::default();
+        for entry in entries {
+            let id = decode_hex(&entry.id)?;
+            let data = decode_hex(&entry.response)?;
+            responder.insert(
+                GioDomain::from_raw(entry.domain),
+                id,
+                GioResponse {
+                    code: entry.code,
+                    data,
+                },
+            );
+        }
+        Ok(responder)
+    }
+
+    pub fn insert(&mut self, domain: GioDomain, id: Vec<u8>, response: GioResponse) {
+        self.responses.insert((domain.raw(), id), response);
+    }
+}
+
+impl GioHandler for JsonResponder {
+    fn handle(&mut self, domain: GioDomain, id: &[u8]) -> Option<GioResponse> {
+        self.responses.get(&(domain.raw(), id.to_vec())).cloned()
+    }
+}
+
+fn decode_hex(s: &str) -> io::Result<Vec<u8>> {
+    hex::decode(s.strip_prefix("0x").unwrap_or(s))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("invalid hex: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::CmtError;
+    use crate::rollup::Rollup;
+
+    const JSON: &str = r#"[
+        { "domain": 42, "id": "0x1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac8", "response": "0x68656c6c6f" },
+        { "domain": 256, "id": "0x01", "code": 1 }
+    ]"#;
+
+    #[test]
+    fn rollup_gio_uses_the_handler() {
+        let mut rollup = Rollup::new().unwrap();
+        rollup.set_gio_handler(JsonResponder::from_slice(JSON.as_bytes()).unwrap());
+
+        let response = rollup
+            .gio(GioDomain::KeccakPreimage, &keccak::hash(b"hello"))
+            .unwrap();
+        assert_eq!(response.data, b"hello");
+
+        assert!(matches!(
+            rollup.gio(GioDomain::Custom(256), &[1]),
+            Err(CmtError::Rejected { code: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn handlers_are_tried_in_order() {
+        let mut store = PreimageStore::new();
+        let hash = store.insert(b"from the store".to_vec());
+        let mut responder = JsonResponder::default();
+        responder.insert(
+            GioDomain::Custom(7),
+            vec![7],
+            GioResponse {
+                code: 0,
+                data: vec![0x77],
+            },
+        );
+        let mut handlers: Vec<Box<dyn GioHandler>> = vec![Box::new(store), Box::new(responder)];
+
+        let preimage = handlers.handle(GioDomain::KeccakPreimage, &hash).unwrap();
+        assert_eq!(preimage.data, b"from the store");
+        let custom = handlers.handle(GioDomain::Custom(7), &[7]).unwrap();
+        assert_eq!(custom.data, [0x77]);
+        assert!(handlers.handle(GioDomain::Custom(8), &[7]).is_none());
+    }
+}
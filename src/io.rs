@@ -1,33 +1,258 @@
-use std::{io, mem::MaybeUninit, slice};
+use std::ops::{Deref, DerefMut};
+use std::{mem::MaybeUninit, slice};
 
+use crate::buffer_len;
+use crate::error::{CmtError, check};
 use crate::generated::*;
-use crate::{buffer_len, to_io_result};
 
+/// The HTIF yield command: whether the machine resumes on its own or waits
+/// for the host to reply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Automatic,
+    Manual,
+}
+
+impl Command {
+    pub fn raw(self) -> u8 {
+        match self {
+            Self::Automatic => HTIF_YIELD_CMD_AUTOMATIC as u8,
+            Self::Manual => HTIF_YIELD_CMD_MANUAL as u8,
+        }
+    }
+}
+
+/// The reason of a yield, or of the host's reply to a manual yield.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    /// Reply: the RX buffer holds an advance request.
+    Advance,
+    /// Reply: the RX buffer holds an inspect request.
+    Inspect,
+    TxVoucher,
+    TxNotice,
+    TxReport,
+    TxException,
+    Progress,
+    RxAccepted,
+    RxRejected,
+    /// A generic IO request to the given domain.
+    Gio(u16),
+}
+
+impl Reason {
+    /// The command a yield with this reason must use, or `None` for reasons
+    /// only the host replies with.
+    pub fn command(self) -> Option<Command> {
+        match self {
+            Self::Advance | Self::Inspect => None,
+            Self::TxVoucher | Self::TxNotice | Self::TxReport | Self::Progress => {
+                Some(Command::Automatic)
+            }
+            Self::TxException | Self::RxAccepted | Self::RxRejected | Self::Gio(_) => {
+                Some(Command::Manual)
+            }
+        }
+    }
+
+    pub fn raw(self) -> u16 {
+        (match self {
+            Self::Advance => HTIF_YIELD_REASON_ADVANCE as u32,
+            Self::Inspect => HTIF_YIELD_REASON_INSPECT as u32,
+            // Vouchers and notices are both outputs to the host.
+            Self::TxVoucher | Self::TxNotice => HTIF_YIELD_AUTOMATIC_REASON_TX_OUTPUT as u32,
+            Self::TxReport => HTIF_YIELD_AUTOMATIC_REASON_TX_REPORT as u32,
+            Self::Progress => HTIF_YIELD_AUTOMATIC_REASON_PROGRESS as u32,
+            Self::TxException => HTIF_YIELD_MANUAL_REASON_TX_EXCEPTION as u32,
+            Self::RxAccepted => HTIF_YIELD_MANUAL_REASON_RX_ACCEPTED as u32,
+            Self::RxRejected => HTIF_YIELD_MANUAL_REASON_RX_REJECTED as u32,
+            Self::Gio(domain) => domain as u32,
+        }) as u16
+    }
+}
+
+/// What the host wrote back after a yield.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct YieldResponse {
+    /// The raw reply reason; a response code for GIO requests.
+    pub reason: u16,
+    /// Length of the reply in the RX buffer.
+    pub data_len: u32,
+}
+
+impl YieldResponse {
+    /// The kind of request the host sent in reply to an accept or reject.
+    pub fn request(&self) -> Option<Reason> {
+        match self.reason as u32 {
+            r if r == HTIF_YIELD_REASON_ADVANCE as u32 => Some(Reason::Advance),
+            r if r == HTIF_YIELD_REASON_INSPECT as u32 => Some(Reason::Inspect),
+            _ => None,
+        }
+    }
+}
+
+/// Driver for the HTIF yield device and the RX/TX buffers it shares with
+/// the host.
+///
+/// The buffers are only reachable through [`RxBuffer`] and [`TxBuffer`]
+/// guards borrowed from the driver, so the borrow checker ends every view
+/// before a yield lets the host overwrite them:
+///
+/// ```compile_fail,E0502
+/// # use libcmt_binding_rust::io::{Command, IoDriver, Reason};
+/// # fn f(driver: &mut IoDriver) {
+/// let rx = driver.rx();
+/// driver.yield_typed(Command::Automatic, Reason::Progress, 0).unwrap();
+/// let _ = rx[0];
+/// # }
+/// ```
+///
+/// ```compile_fail,E0499
+/// # use libcmt_binding_rust::io::{Command, IoDriver, Reason};
+/// # fn f(driver: &mut IoDriver) {
+/// let mut tx = driver.tx();
+/// driver.yield_typed(Command::Automatic, Reason::TxReport, 1).unwrap();
+/// tx[0] = 1;
+/// # }
+/// ```
+///
+/// Views that end before the yield are fine:
+///
+/// ```no_run
+/// # use libcmt_binding_rust::io::{Command, IoDriver, Reason};
+/// # fn f(driver: &mut IoDriver) {
+/// let (rx, mut tx) = driver.buffers();
+/// tx[..4].copy_from_slice(&rx[..4]);
+/// driver.yield_typed(Command::Automatic, Reason::TxReport, 4).unwrap();
+/// # }
+/// ```
 pub struct IoDriver {
     inner: cmt_io_driver_t,
+    rx: cmt_buf_t,
+    tx: cmt_buf_t,
+}
+
+/// Read-only view of the RX buffer, valid until the next yield.
+pub struct RxBuffer<'a> {
+    data: &'a [u8],
+}
+
+/// Writable view of the TX buffer, valid until the next yield.
+pub struct TxBuffer<'a> {
+    data: &'a mut [u8],
+}
+
+impl Deref for RxBuffer<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.data
+    }
+}
+
+impl Deref for TxBuffer<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.data
+    }
+}
+
+impl DerefMut for TxBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.data
+    }
 }
 
 impl IoDriver {
-    pub fn new() -> io::Result<Self> {
+    pub fn new() -> Result<Self, CmtError> {
         let mut driver = MaybeUninit::<cmt_io_driver_t>::uninit();
         let rc = unsafe { cmt_io_init(driver.as_mut_ptr()) };
-        to_io_result(rc)?;
-        let inner = unsafe { driver.assume_init() };
-        Ok(Self { inner })
+        check(rc, "cmt_io_init")?;
+        let mut inner = unsafe { driver.assume_init() };
+        // The buffers are mapped once at init and stay put.
+        let rx = unsafe { cmt_io_get_rx(&mut inner) };
+        let tx = unsafe { cmt_io_get_tx(&mut inner) };
+        Ok(Self { inner, rx, tx })
+    }
+
+    pub fn rx(&self) -> RxBuffer<'_> {
+        RxBuffer {
+            data: unsafe { slice::from_raw_parts(self.rx.begin, buffer_len(&self.rx)) },
+        }
     }
 
-    pub fn tx_buffer(&mut self) -> &mut [u8] {
-        let buf = unsafe { cmt_io_get_tx(&mut self.inner) };
-        unsafe { slice::from_raw_parts_mut(buf.begin, buffer_len(&buf)) }
+    pub fn tx(&mut self) -> TxBuffer<'_> {
+        TxBuffer {
+            data: unsafe { slice::from_raw_parts_mut(self.tx.begin, buffer_len(&self.tx)) },
+        }
     }
 
-    pub fn rx_buffer(&mut self) -> &[u8] {
-        let buf = unsafe { cmt_io_get_rx(&mut self.inner) };
-        unsafe { slice::from_raw_parts(buf.begin, buffer_len(&buf)) }
+    /// Both buffers at once, e.g. to copy a request into a reply. They are
+    /// distinct memory regions.
+    pub fn buffers(&mut self) -> (RxBuffer<'_>, TxBuffer<'_>) {
+        let rx = unsafe { slice::from_raw_parts(self.rx.begin, buffer_len(&self.rx)) };
+        let tx = unsafe { slice::from_raw_parts_mut(self.tx.begin, buffer_len(&self.tx)) };
+        (RxBuffer { data: rx }, TxBuffer { data: tx })
     }
 
-    pub fn yield_request(&mut self, request: &mut cmt_io_yield_t) -> io::Result<()> {
-        to_io_result(unsafe { cmt_io_yield(&mut self.inner, request) })
+    pub fn yield_request(&mut self, request: &mut cmt_io_yield_t) -> Result<(), CmtError> {
+        check(
+            unsafe { cmt_io_yield(&mut self.inner, request) },
+            "cmt_io_yield",
+        )
+    }
+
+    /// Yields to the host with `data_len` bytes of the TX buffer as payload.
+    /// A `data_len` past the end of the TX buffer is
+    /// [`CmtError::BufferTooSmall`].
+    pub fn yield_typed(
+        &mut self,
+        cmd: Command,
+        reason: Reason,
+        data_len: u32,
+    ) -> Result<YieldResponse, CmtError> {
+        const OP: &str = "cmt_io_yield";
+        match reason.command() {
+            Some(expected) if expected == cmd => {}
+            Some(expected) => {
+                return Err(CmtError::invalid_input(
+                    OP,
+                    format!("{reason:?} requires a {expected:?} yield, got {cmd:?}"),
+                ));
+            }
+            None => {
+                return Err(CmtError::invalid_input(
+                    OP,
+                    format!("{reason:?} is only sent by the host"),
+                ));
+            }
+        }
+
+        // Every reason a guest sends except progress points the host at the
+        // first `data_len` bytes of the TX buffer.
+        if reason != Reason::Progress {
+            let available = buffer_len(&self.tx);
+            if data_len as usize > available {
+                return Err(CmtError::BufferTooSmall {
+                    op: OP,
+                    requested: data_len as usize,
+                    available,
+                });
+            }
+        }
+
+        let mut request = cmt_io_yield_t {
+            dev: HTIF_DEVICE_YIELD as u8,
+            cmd: cmd.raw(),
+            reason: reason.raw(),
+            data: data_len,
+        };
+        self.yield_request(&mut request)?;
+        Ok(YieldResponse {
+            reason: request.reason,
+            data_len: request.data,
+        })
     }
 }
 
@@ -30,3 +30,10 @@ pub fn funsel(decl: &str) -> u32 {
     let c_decl = CString::new(decl).expect("ABI declaration must be null free");
     unsafe { cmt_keccak_funsel(c_decl.as_ptr()) }
 }
+
+/// Returns the Keccak-256 hash of `input`.
+pub fn hash(input: &[u8]) -> [u8; KECCAK_LEN] {
+    let mut out = [0u8; KECCAK_LEN];
+    data(input.len(), input, &mut out);
+    out
+}
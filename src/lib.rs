@@ -33,8 +33,16 @@ pub(crate) fn buffer_len(buf: &cmt_buf_t) -> usize {
 
 pub mod abi;
 pub mod buf;
+pub mod error;
+pub mod gio;
 pub mod io;
 pub mod keccak;
 pub mod merkle;
+pub mod notices;
+pub mod outputs;
+pub mod portals;
+pub mod progress;
+pub mod reports;
 pub mod rollup;
 pub mod util;
+pub mod vouchers;
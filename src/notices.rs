@@ -0,0 +1,331 @@
+//! Structured notice payloads.
+//!
+//! A notice built from an [`EventNotice`] carries
+//! `abi.encode(bytes32 topic0, bytes32[] topics, bytes data)`, where `topic0`
+//! is the Keccak-256 hash of a Solidity event signature and `topics` holds the
+//! indexed arguments, mirroring the layout of an EVM log.
+//!
+//! Payloads too large for a single notice are split into [`NoticeChunk`]s,
+//! each carrying `abi.encode(uint64 id, uint32 index, uint32 total,
+//! bytes32 hash, bytes data)`, where `hash` is the Keccak-256 hash of the
+//! whole payload. A [`NoticeReassembler`] puts them back together off-chain.
+
+use std::collections::{BTreeMap, HashMap};
+use std::{error, fmt};
+
+use crate::abi::{self, AbiError, Address, B256, Bytes, U256};
+use crate::keccak;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventNotice {
+    pub topic0: B256,
+    pub topics: Vec<B256>,
+    pub data: Vec<u8>,
+}
+
+impl EventNotice {
+    /// Builds a notice for the event `signature`, e.g.
+    /// `Transfer(address,address,uint256)`.
+    pub fn new(signature: &str, topics: Vec<B256>, data: Vec<u8>) -> Self {
+        Self {
+            topic0: event_topic(signature),
+            topics,
+            data,
+        }
+    }
+
+    /// Whether the notice was built for the event `signature`.
+    pub fn is(&self, signature: &str) -> bool {
+        self.topic0 == event_topic(signature)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        abi::encode_params(&(&self.topic0, &self.topics, &self.data[..]))
+    }
+
+    pub fn decode(payload: &[u8]) -> Result<Self, AbiError> {
+        let (topic0, topics, data): (B256, Vec<B256>, Bytes) = abi::decode_params(payload)?;
+        Ok(Self {
+            topic0,
+            topics,
+            data: data.0,
+        })
+    }
+}
+
+/// The first topic of an event: the Keccak-256 hash of its signature.
+pub fn event_topic(signature: &str) -> B256 {
+    B256::new(keccak::hash(signature.as_bytes()))
+}
+
+/// An indexed `address` argument, left padded to 32 bytes.
+pub fn address_topic(address: &Address) -> B256 {
+    let mut topic = [0u8; 32];
+    topic[12..].copy_from_slice(&address.0);
+    B256::new(topic)
+}
+
+/// An indexed `uintN` argument.
+pub fn uint_topic<T: Into<U256>>(value: T) -> B256 {
+    B256::new(value.into().0)
+}
+
+/// An indexed dynamic argument (`bytes`, `string`, arrays), which events
+/// store as the Keccak-256 hash of its value.
+pub fn hashed_topic(value: &[u8]) -> B256 {
+    B256::new(keccak::hash(value))
+}
+
+/// One frame of a payload split across several notices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoticeChunk {
+    /// Identifies the payload the chunk belongs to.
+    pub id: u64,
+    pub index: u32,
+    pub total: u32,
+    /// Keccak-256 hash of the whole payload.
+    pub hash: B256,
+    pub data: Vec<u8>,
+}
+
+impl NoticeChunk {
+    /// Bytes the frame adds around `data`: five head words and the length
+    /// word of `data`.
+    pub const OVERHEAD: usize = 6 * 32;
+
+    /// Splits `payload` into chunks of at most `chunk_len` bytes. An empty
+    /// payload still yields one chunk. Fails if `chunk_len` is zero or so
+    /// small that the chunk count does not fit in a `uint32`.
+    pub fn split(id: u64, payload: &[u8], chunk_len: usize) -> Result<Vec<Self>, ChunkError> {
+        if chunk_len == 0 {
+            return Err(ChunkError::InvalidChunkLen { chunk_len });
+        }
+        let hash = B256::new(keccak::hash(payload));
+        let parts: Vec<&[u8]> = if payload.is_empty() {
+            vec![payload]
+        } else {
+            payload.chunks(chunk_len).collect()
+        };
+        let total =
+            u32::try_from(parts.len()).map_err(|_| ChunkError::InvalidChunkLen { chunk_len })?;
+        Ok(parts
+            .into_iter()
+            .zip(0..)
+            .map(|(data, index)| Self {
+                id,
+                index,
+                total,
+                hash,
+                data: data.to_vec(),
+            })
+            .collect())
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        abi::encode_params(&(self.id, self.index, self.total, &self.hash, &self.data[..]))
+    }
+
+    pub fn decode(payload: &[u8]) -> Result<Self, AbiError> {
+        let (id, index, total, hash, data): (u64, u32, u32, B256, Bytes) =
+            abi::decode_params(payload)?;
+        Ok(Self {
+            id,
+            index,
+            total,
+            hash,
+            data: data.0,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum ChunkError {
+    /// The notice is not a chunk frame.
+    Decode(AbiError),
+    /// A payload cannot be split into chunks of this length.
+    InvalidChunkLen { chunk_len: usize },
+    /// The chunk index is not below its total, or the total is zero.
+    IndexOutOfRange { id: u64, index: u32, total: u32 },
+    /// The chunk disagrees with earlier chunks of the same id on the total,
+    /// the hash or the data at its index.
+    Inconsistent { id: u64, index: u32 },
+    /// The reassembled payload does not hash to the announced value.
+    HashMismatch { id: u64 },
+}
+
+impl fmt::Display for ChunkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Decode(e) => write!(f, "invalid notice chunk: {e}"),
+            Self::InvalidChunkLen { chunk_len } => {
+                write!(f, "cannot split a payload into chunks of {chunk_len} bytes")
+            }
+            Self::IndexOutOfRange { id, index, total } => {
+                write!(
+                    f,
+                    "chunk {index} of {total} is out of range for payload {id}"
+                )
+            }
+            Self::Inconsistent { id, index } => {
+                write!(
+                    f,
+                    "chunk {index} conflicts with earlier chunks of payload {id}"
+                )
+            }
+            Self::HashMismatch { id } => write!(f, "payload {id} does not match its hash"),
+        }
+    }
+}
+
+impl error::Error for ChunkError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<AbiError> for ChunkError {
+    fn from(e: AbiError) -> Self {
+        Self::Decode(e)
+    }
+}
+
+/// Chunks received so far for one payload, keyed by index. The map only
+/// grows with the chunks that actually arrive, so a forged `total` cannot
+/// make the reassembler allocate up front.
+#[derive(Debug)]
+struct Pending {
+    total: u32,
+    hash: B256,
+    parts: BTreeMap<u32, Vec<u8>>,
+}
+
+/// Collects notice chunks, in any order and interleaved across payloads,
+/// and returns each payload once all of its chunks have arrived.
+#[derive(Debug, Default)]
+pub struct NoticeReassembler {
+    pending: HashMap<u64, Pending>,
+}
+
+impl NoticeReassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds one notice payload. Returns the full payload, verified against
+    /// its hash, when `notice` is the last missing chunk.
+    pub fn push(&mut self, notice: &[u8]) -> Result<Option<Vec<u8>>, ChunkError> {
+        self.push_chunk(NoticeChunk::decode(notice)?)
+    }
+
+    pub fn push_chunk(&mut self, chunk: NoticeChunk) -> Result<Option<Vec<u8>>, ChunkError> {
+        let NoticeChunk {
+            id,
+            index,
+            total,
+            hash,
+            data,
+        } = chunk;
+        if index >= total {
+            return Err(ChunkError::IndexOutOfRange { id, index, total });
+        }
+
+        let pending = self.pending.entry(id).or_insert_with(|| Pending {
+            total,
+            hash,
+            parts: BTreeMap::new(),
+        });
+        if pending.total != total || pending.hash != hash {
+            return Err(ChunkError::Inconsistent { id, index });
+        }
+        match pending.parts.get(&index) {
+            Some(existing) if *existing != data => {
+                return Err(ChunkError::Inconsistent { id, index });
+            }
+            Some(_) => return Ok(None),
+            None => {
+                pending.parts.insert(index, data);
+            }
+        }
+        if pending.parts.len() < total as usize {
+            return Ok(None);
+        }
+
+        let pending = self.pending.remove(&id).unwrap();
+        let payload: Vec<u8> = pending.parts.into_values().flatten().collect();
+        if keccak::hash(&payload) != pending.hash.0 {
+            return Err(ChunkError::HashMismatch { id });
+        }
+        Ok(Some(payload))
+    }
+
+    /// Number of payloads with chunks still missing.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSFER: &str = "Transfer(address,address,uint256)";
+
+    #[test]
+    fn transfer_topic() {
+        assert_eq!(
+            hex::encode(event_topic(TRANSFER).0),
+            "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        );
+    }
+
+    #[test]
+    fn event_notice_round_trip() {
+        let from = B256::new([0x11; 32]);
+        let to = B256::new([0x22; 32]);
+        let notice = EventNotice::new(TRANSFER, vec![from, to], vec![0xab; 33]);
+        let decoded = EventNotice::decode(&notice.encode()).unwrap();
+        assert!(decoded.is(TRANSFER));
+        assert_eq!(decoded, notice);
+    }
+
+    #[test]
+    fn chunks_reassemble_in_any_order() {
+        let payload: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let mut chunks = NoticeChunk::split(7, &payload, 300).unwrap();
+        assert_eq!(chunks.len(), 4);
+        chunks.reverse();
+
+        let mut reassembler = NoticeReassembler::new();
+        let (last, rest) = chunks.split_last().unwrap();
+        for chunk in rest {
+            assert_eq!(reassembler.push(&chunk.encode()).unwrap(), None);
+        }
+        assert_eq!(reassembler.push(&last.encode()).unwrap(), Some(payload));
+        assert_eq!(reassembler.pending(), 0);
+    }
+
+    #[test]
+    fn zero_chunk_len_is_an_error() {
+        assert!(matches!(
+            NoticeChunk::split(0, b"payload", 0),
+            Err(ChunkError::InvalidChunkLen { chunk_len: 0 })
+        ));
+    }
+
+    #[test]
+    fn huge_total_does_not_preallocate() {
+        let chunk = NoticeChunk {
+            id: 1,
+            index: 0,
+            total: u32::MAX,
+            hash: B256::new([0; 32]),
+            data: vec![1, 2, 3],
+        };
+        let mut reassembler = NoticeReassembler::new();
+        assert_eq!(reassembler.push_chunk(chunk).unwrap(), None);
+        assert_eq!(reassembler.pending(), 1);
+    }
+}
@@ -0,0 +1,278 @@
+//! Output encodings and the journal of outputs emitted by a [`Rollup`].
+//!
+//! libcmt stores each voucher, delegate call voucher and notice in the
+//! outputs merkle tree as the ABI encoded call below; the Keccak-256 hash of
+//! that encoding is the leaf an on-chain proof refers to. The
+//! `encode_*_output` functions produce the same bytes without emitting.
+//!
+//! | Output                | Encoding |
+//! |-----------------------|----------|
+//! | voucher               | `Voucher(address destination, uint256 value, bytes payload)` |
+//! | delegate call voucher | `DelegateCallVoucher(address destination, bytes payload)` |
+//! | notice                | `Notice(bytes payload)` |
+//!
+//! [`Rollup`]: crate::rollup::Rollup
+
+use std::mem::MaybeUninit;
+
+use serde_json::{Value, json};
+
+use crate::abi::{AbiEncoder, Address, B256, U256};
+use crate::generated::cmt_merkle_t;
+use crate::rollup::OutputKind;
+use crate::{keccak, merkle};
+
+/// `Voucher(address,uint256,bytes)`, `0x237a816f`.
+pub const VOUCHER_FUNSEL: u32 = u32::from_le_bytes([0x23, 0x7a, 0x81, 0x6f]);
+/// `DelegateCallVoucher(address,bytes)`, `0x10321e8b`.
+pub const DELEGATE_CALL_VOUCHER_FUNSEL: u32 = u32::from_le_bytes([0x10, 0x32, 0x1e, 0x8b]);
+/// `Notice(bytes)`, `0xc258d6e5`.
+pub const NOTICE_FUNSEL: u32 = u32::from_le_bytes([0xc2, 0x58, 0xd6, 0xe5]);
+
+/// The bytes of an output as libcmt stores them, and their Keccak-256 hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedOutput {
+    pub bytes: Vec<u8>,
+    pub hash: B256,
+}
+
+impl EncodedOutput {
+    fn new(bytes: Vec<u8>) -> Self {
+        let hash = B256::new(keccak::hash(&bytes));
+        Self { bytes, hash }
+    }
+}
+
+/// Encodes a voucher the way `cmt_rollup_emit_voucher` does, without
+/// emitting it.
+pub fn encode_voucher_output(destination: &Address, value: &U256, payload: &[u8]) -> EncodedOutput {
+    let mut enc = AbiEncoder::with_funsel(VOUCHER_FUNSEL);
+    enc.put_address(destination)
+        .put_uint256(value)
+        .put_bytes(payload);
+    EncodedOutput::new(enc.finish())
+}
+
+/// Encodes a delegate call voucher the way
+/// `cmt_rollup_emit_delegate_call_voucher` does, without emitting it.
+pub fn encode_delegate_call_voucher_output(destination: &Address, payload: &[u8]) -> EncodedOutput {
+    let mut enc = AbiEncoder::with_funsel(DELEGATE_CALL_VOUCHER_FUNSEL);
+    enc.put_address(destination).put_bytes(payload);
+    EncodedOutput::new(enc.finish())
+}
+
+/// Encodes a notice the way `cmt_rollup_emit_notice` does, without
+/// emitting it.
+pub fn encode_notice_output(payload: &[u8]) -> EncodedOutput {
+    let mut enc = AbiEncoder::with_funsel(NOTICE_FUNSEL);
+    enc.put_bytes(payload);
+    EncodedOutput::new(enc.finish())
+}
+
+/// An output as emitted by the application.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Output {
+    Voucher {
+        destination: Address,
+        value: U256,
+        payload: Vec<u8>,
+    },
+    DelegateCallVoucher {
+        destination: Address,
+        payload: Vec<u8>,
+    },
+    Notice {
+        payload: Vec<u8>,
+    },
+}
+
+impl Output {
+    pub fn kind(&self) -> OutputKind {
+        match self {
+            Self::Voucher { .. } => OutputKind::Voucher,
+            Self::DelegateCallVoucher { .. } => OutputKind::DelegateCallVoucher,
+            Self::Notice { .. } => OutputKind::Notice,
+        }
+    }
+
+    pub fn encode(&self) -> EncodedOutput {
+        match self {
+            Self::Voucher {
+                destination,
+                value,
+                payload,
+            } => encode_voucher_output(destination, value, payload),
+            Self::DelegateCallVoucher {
+                destination,
+                payload,
+            } => encode_delegate_call_voucher_output(destination, payload),
+            Self::Notice { payload } => encode_notice_output(payload),
+        }
+    }
+}
+
+/// The root of the outputs merkle tree after `outputs` are emitted in
+/// order, as kept by the machine and written by
+/// [`Rollup::save_merkle`](crate::rollup::Rollup::save_merkle).
+///
+/// # Panics
+///
+/// If `outputs` overflow the tree. libcmt's tree holds 2^63 leaves, more
+/// than a slice can hold, so this does not happen in practice.
+pub fn outputs_root(outputs: &[Output]) -> B256 {
+    // `cmt_merkle_t` is plain data; `init` sets up the empty tree.
+    let mut tree: cmt_merkle_t = unsafe { MaybeUninit::zeroed().assume_init() };
+    merkle::init(&mut tree);
+    for output in outputs {
+        merkle::push_back(&mut tree, &output.encode().hash.0).expect("outputs merkle tree is full");
+    }
+    let mut root = [0u8; 32];
+    merkle::root_hash(&mut tree, &mut root);
+    merkle::fini(&mut tree);
+    B256::new(root)
+}
+
+/// One emitted output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub kind: OutputKind,
+    /// The output index returned by libcmt.
+    pub index: u64,
+    /// Keccak-256 hash of the output's ABI encoding.
+    pub hash: B256,
+    /// Index of the advance input being processed, if one was read.
+    pub input_index: Option<u64>,
+}
+
+impl JournalEntry {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "kind": format!("{:?}", self.kind),
+            "index": self.index,
+            "hash": format!("0x{}", hex::encode(self.hash.0)),
+            "input_index": self.input_index,
+        })
+    }
+}
+
+/// The outputs emitted so far, in order. See
+/// [`Rollup::enable_journal`](crate::rollup::Rollup::enable_journal).
+#[derive(Debug, Clone, Default)]
+pub struct OutputJournal {
+    entries: Vec<JournalEntry>,
+}
+
+impl OutputJournal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, entry: JournalEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    /// The outputs emitted while processing input `input_index`.
+    pub fn for_input(&self, input_index: u64) -> impl Iterator<Item = &JournalEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.input_index == Some(input_index))
+    }
+
+    /// The output with index `index`.
+    pub fn find(&self, index: u64) -> Option<&JournalEntry> {
+        self.entries.iter().find(|entry| entry.index == index)
+    }
+
+    /// The outputs of input `input_index` as a JSON array.
+    pub fn dump_input(&self, input_index: u64) -> Value {
+        Value::Array(
+            self.for_input(input_index)
+                .map(JournalEntry::to_json)
+                .collect(),
+        )
+    }
+
+    /// All outputs as a JSON array.
+    pub fn dump(&self) -> Value {
+        Value::Array(self.entries.iter().map(JournalEntry::to_json).collect())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::*;
+    use crate::rollup::Rollup;
+
+    #[test]
+    fn outputs_root_matches_saved_merkle() {
+        let outputs = [
+            Output::Voucher {
+                destination: Address::new([0x11; 20]),
+                value: U256::from(7u64),
+                payload: b"transfer".to_vec(),
+            },
+            Output::Notice {
+                payload: b"a notice longer than a single 32-byte ABI word".to_vec(),
+            },
+            Output::DelegateCallVoucher {
+                destination: Address::new([0x22; 20]),
+                payload: vec![0xab; 4],
+            },
+        ];
+
+        let mut rollup = Rollup::new().unwrap();
+        for output in &outputs {
+            match output {
+                Output::Voucher {
+                    destination,
+                    value,
+                    payload,
+                } => rollup.emit_voucher(
+                    &format!("0x{}", hex::encode(destination.0)),
+                    Some(&format!("0x{}", hex::encode(value.0))),
+                    &format!("0x{}", hex::encode(payload)),
+                ),
+                Output::DelegateCallVoucher {
+                    destination,
+                    payload,
+                } => rollup.emit_delegate_call_voucher_bytes(destination, payload),
+                Output::Notice { payload } => rollup.emit_notice_bytes(payload),
+            }
+            .unwrap();
+        }
+
+        let path = env::temp_dir().join(format!("cmt-outputs-root-{}.bin", process::id()));
+        rollup.save_merkle(&path).unwrap();
+        let mut tree: cmt_merkle_t = unsafe { MaybeUninit::zeroed().assume_init() };
+        merkle::init(&mut tree);
+        let loaded = merkle::load(
+            &mut tree,
+            &crate::path_to_cstring(path.as_os_str()).unwrap(),
+        );
+        fs::remove_file(&path).unwrap();
+        loaded.unwrap();
+        let mut saved = [0u8; 32];
+        merkle::root_hash(&mut tree, &mut saved);
+        merkle::fini(&mut tree);
+
+        assert_eq!(outputs_root(&outputs), B256::new(saved));
+    }
+}
@@ -0,0 +1,403 @@
+//! Decoders for the deposit inputs sent by the Cartesi Rollups portals.
+//!
+//! Portal payloads are `abi.encodePacked` encodings; the ERC-721 and ERC-1155
+//! portals append a standard ABI encoded tail for their dynamic fields.
+
+use crate::abi::packed::{PackedDecoder, PackedEncoder};
+use crate::abi::{self, AbiError, Address, Bytes, U256};
+
+pub const ERC1155_BATCH_PORTAL: Address = address("0xc700A2e5531E720a2434433b6ccf4c0eA2400051");
+pub const ERC1155_SINGLE_PORTAL: Address = address("0xc700A261279aFC6F755A3a67D86ae43E2eBD0051");
+pub const ERC20_PORTAL: Address = address("0xc700D6aDd016eECd59d989C028214Eaa0fCC0051");
+pub const ERC721_PORTAL: Address = address("0xc700d52F5290e978e9CAe7D1E092935263b60051");
+pub const ETHER_PORTAL: Address = address("0xc70076a466789B595b50959cdc261227F0D70051");
+
+const fn address(s: &str) -> Address {
+    const fn nibble(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            b'A'..=b'F' => c - b'A' + 10,
+            _ => panic!("invalid hex digit"),
+        }
+    }
+    let s = s.as_bytes();
+    let mut out = [0u8; 20];
+    let mut i = 0;
+    while i < 20 {
+        out[i] = (nibble(s[2 + 2 * i]) << 4) | nibble(s[3 + 2 * i]);
+        i += 1;
+    }
+    Address(out)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Portal {
+    Erc1155Batch,
+    Erc1155Single,
+    Erc20,
+    Erc721,
+    Ether,
+}
+
+impl Portal {
+    /// Identifies the portal by the `msg_sender` of an advance input.
+    pub fn from_address(address: &Address) -> Option<Self> {
+        match *address {
+            ERC1155_BATCH_PORTAL => Some(Self::Erc1155Batch),
+            ERC1155_SINGLE_PORTAL => Some(Self::Erc1155Single),
+            ERC20_PORTAL => Some(Self::Erc20),
+            ERC721_PORTAL => Some(Self::Erc721),
+            ETHER_PORTAL => Some(Self::Ether),
+            _ => None,
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        match self {
+            Self::Erc1155Batch => ERC1155_BATCH_PORTAL,
+            Self::Erc1155Single => ERC1155_SINGLE_PORTAL,
+            Self::Erc20 => ERC20_PORTAL,
+            Self::Erc721 => ERC721_PORTAL,
+            Self::Ether => ETHER_PORTAL,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EtherDeposit {
+    pub sender: Address,
+    pub value: U256,
+    pub exec_layer_data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Erc20Deposit {
+    pub token: Address,
+    pub sender: Address,
+    pub value: U256,
+    pub exec_layer_data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Erc721Deposit {
+    pub token: Address,
+    pub sender: Address,
+    pub token_id: U256,
+    pub base_layer_data: Vec<u8>,
+    pub exec_layer_data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Erc1155SingleDeposit {
+    pub token: Address,
+    pub sender: Address,
+    pub token_id: U256,
+    pub value: U256,
+    pub base_layer_data: Vec<u8>,
+    pub exec_layer_data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Erc1155BatchDeposit {
+    pub token: Address,
+    pub sender: Address,
+    pub token_ids: Vec<U256>,
+    pub values: Vec<U256>,
+    pub base_layer_data: Vec<u8>,
+    pub exec_layer_data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Deposit {
+    Ether(EtherDeposit),
+    Erc20(Erc20Deposit),
+    Erc721(Erc721Deposit),
+    Erc1155Single(Erc1155SingleDeposit),
+    Erc1155Batch(Erc1155BatchDeposit),
+}
+
+impl Deposit {
+    /// Decodes `payload` if `msg_sender` is one of the portals, returning
+    /// `Ok(None)` for inputs sent by anyone else.
+    pub fn decode(msg_sender: &Address, payload: &[u8]) -> Result<Option<Self>, AbiError> {
+        Portal::from_address(msg_sender)
+            .map(|portal| Self::decode_from(portal, payload))
+            .transpose()
+    }
+
+    pub fn decode_from(portal: Portal, payload: &[u8]) -> Result<Self, AbiError> {
+        Ok(match portal {
+            Portal::Ether => Self::Ether(EtherDeposit::decode(payload)?),
+            Portal::Erc20 => Self::Erc20(Erc20Deposit::decode(payload)?),
+            Portal::Erc721 => Self::Erc721(Erc721Deposit::decode(payload)?),
+            Portal::Erc1155Single => Self::Erc1155Single(Erc1155SingleDeposit::decode(payload)?),
+            Portal::Erc1155Batch => Self::Erc1155Batch(Erc1155BatchDeposit::decode(payload)?),
+        })
+    }
+
+    pub fn portal(&self) -> Portal {
+        match self {
+            Self::Ether(_) => Portal::Ether,
+            Self::Erc20(_) => Portal::Erc20,
+            Self::Erc721(_) => Portal::Erc721,
+            Self::Erc1155Single(_) => Portal::Erc1155Single,
+            Self::Erc1155Batch(_) => Portal::Erc1155Batch,
+        }
+    }
+
+    /// Encodes the deposit as the portal would send it.
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::Ether(d) => d.encode(),
+            Self::Erc20(d) => d.encode(),
+            Self::Erc721(d) => d.encode(),
+            Self::Erc1155Single(d) => d.encode(),
+            Self::Erc1155Batch(d) => d.encode(),
+        }
+    }
+}
+
+impl EtherDeposit {
+    /// `abi.encodePacked(sender, value, execLayerData)`
+    pub fn decode(payload: &[u8]) -> Result<Self, AbiError> {
+        let mut dec = PackedDecoder::new(payload);
+        Ok(Self {
+            sender: dec.get_address()?,
+            value: dec.get_uint256()?,
+            exec_layer_data: dec.rest().to_vec(),
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut enc = PackedEncoder::new();
+        enc.put_address(&self.sender)
+            .put_uint256(&self.value)
+            .put_bytes(&self.exec_layer_data);
+        enc.finish()
+    }
+}
+
+impl Erc20Deposit {
+    /// `abi.encodePacked(token, sender, value, execLayerData)`
+    pub fn decode(payload: &[u8]) -> Result<Self, AbiError> {
+        let mut dec = PackedDecoder::new(payload);
+        Ok(Self {
+            token: dec.get_address()?,
+            sender: dec.get_address()?,
+            value: dec.get_uint256()?,
+            exec_layer_data: dec.rest().to_vec(),
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut enc = PackedEncoder::new();
+        enc.put_address(&self.token)
+            .put_address(&self.sender)
+            .put_uint256(&self.value)
+            .put_bytes(&self.exec_layer_data);
+        enc.finish()
+    }
+}
+
+impl Erc721Deposit {
+    /// `abi.encodePacked(token, sender, tokenId, abi.encode(baseLayerData, execLayerData))`
+    pub fn decode(payload: &[u8]) -> Result<Self, AbiError> {
+        let mut dec = PackedDecoder::new(payload);
+        let token = dec.get_address()?;
+        let sender = dec.get_address()?;
+        let token_id = dec.get_uint256()?;
+        let (base_layer_data, exec_layer_data): (Bytes, Bytes) = abi::decode_params(dec.rest())?;
+        Ok(Self {
+            token,
+            sender,
+            token_id,
+            base_layer_data: base_layer_data.0,
+            exec_layer_data: exec_layer_data.0,
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut enc = PackedEncoder::new();
+        enc.put_address(&self.token)
+            .put_address(&self.sender)
+            .put_uint256(&self.token_id)
+            .put_bytes(&abi::encode_params(&(
+                &self.base_layer_data[..],
+                &self.exec_layer_data[..],
+            )));
+        enc.finish()
+    }
+}
+
+impl Erc1155SingleDeposit {
+    /// `abi.encodePacked(token, sender, tokenId, value, abi.encode(baseLayerData, execLayerData))`
+    pub fn decode(payload: &[u8]) -> Result<Self, AbiError> {
+        let mut dec = PackedDecoder::new(payload);
+        let token = dec.get_address()?;
+        let sender = dec.get_address()?;
+        let token_id = dec.get_uint256()?;
+        let value = dec.get_uint256()?;
+        let (base_layer_data, exec_layer_data): (Bytes, Bytes) = abi::decode_params(dec.rest())?;
+        Ok(Self {
+            token,
+            sender,
+            token_id,
+            value,
+            base_layer_data: base_layer_data.0,
+            exec_layer_data: exec_layer_data.0,
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut enc = PackedEncoder::new();
+        enc.put_address(&self.token)
+            .put_address(&self.sender)
+            .put_uint256(&self.token_id)
+            .put_uint256(&self.value)
+            .put_bytes(&abi::encode_params(&(
+                &self.base_layer_data[..],
+                &self.exec_layer_data[..],
+            )));
+        enc.finish()
+    }
+}
+
+impl Erc1155BatchDeposit {
+    /// `abi.encodePacked(token, sender, abi.encode(tokenIds, values, baseLayerData, execLayerData))`
+    pub fn decode(payload: &[u8]) -> Result<Self, AbiError> {
+        let mut dec = PackedDecoder::new(payload);
+        let token = dec.get_address()?;
+        let sender = dec.get_address()?;
+        let (token_ids, values, base_layer_data, exec_layer_data): (
+            Vec<U256>,
+            Vec<U256>,
+            Bytes,
+            Bytes,
+        ) = abi::decode_params(dec.rest())?;
+        Ok(Self {
+            token,
+            sender,
+            token_ids,
+            values,
+            base_layer_data: base_layer_data.0,
+            exec_layer_data: exec_layer_data.0,
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut enc = PackedEncoder::new();
+        enc.put_address(&self.token)
+            .put_address(&self.sender)
+            .put_bytes(&abi::encode_params(&(
+                &self.token_ids,
+                &self.values,
+                &self.base_layer_data[..],
+                &self.exec_layer_data[..],
+            )));
+        enc.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: Address = Address::new([0x11; 20]);
+    const SENDER: Address = Address::new([0x22; 20]);
+
+    fn round_trip(deposit: Deposit) {
+        let payload = deposit.encode();
+        let portal = deposit.portal().address();
+        assert_eq!(Deposit::decode(&portal, &payload).unwrap(), Some(deposit));
+    }
+
+    #[test]
+    fn ether_round_trip() {
+        round_trip(Deposit::Ether(EtherDeposit {
+            sender: SENDER,
+            value: U256::from(1u64),
+            exec_layer_data: vec![0xab; 3],
+        }));
+    }
+
+    #[test]
+    fn erc20_round_trip() {
+        round_trip(Deposit::Erc20(Erc20Deposit {
+            token: TOKEN,
+            sender: SENDER,
+            value: U256::from(2u64),
+            exec_layer_data: vec![],
+        }));
+    }
+
+    #[test]
+    fn erc721_round_trip() {
+        round_trip(Deposit::Erc721(Erc721Deposit {
+            token: TOKEN,
+            sender: SENDER,
+            token_id: U256::from(3u64),
+            base_layer_data: vec![0xcd; 40],
+            exec_layer_data: vec![0xef],
+        }));
+    }
+
+    #[test]
+    fn erc1155_single_round_trip() {
+        round_trip(Deposit::Erc1155Single(Erc1155SingleDeposit {
+            token: TOKEN,
+            sender: SENDER,
+            token_id: U256::from(4u64),
+            value: U256::from(5u64),
+            base_layer_data: vec![],
+            exec_layer_data: vec![0x01, 0x02],
+        }));
+    }
+
+    #[test]
+    fn erc1155_batch_round_trip() {
+        round_trip(Deposit::Erc1155Batch(Erc1155BatchDeposit {
+            token: TOKEN,
+            sender: SENDER,
+            token_ids: vec![U256::from(6u64), U256::from(7u64)],
+            values: vec![U256::from(8u64), U256::from(9u64)],
+            base_layer_data: vec![0x03],
+            exec_layer_data: vec![],
+        }));
+    }
+
+    #[test]
+    fn erc1155_batch_vector() {
+        // abi.encodePacked(token, sender,
+        //     abi.encode([1, 2], [3, 4], hex"", hex"ab"))
+        let payload = hex::decode(concat!(
+            "1111111111111111111111111111111111111111",
+            "2222222222222222222222222222222222222222",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+            "00000000000000000000000000000000000000000000000000000000000000e0",
+            "0000000000000000000000000000000000000000000000000000000000000140",
+            "0000000000000000000000000000000000000000000000000000000000000160",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000000000000000000000000000000000000000000003",
+            "0000000000000000000000000000000000000000000000000000000000000004",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "ab00000000000000000000000000000000000000000000000000000000000000",
+        ))
+        .unwrap();
+        let deposit = Erc1155BatchDeposit {
+            token: TOKEN,
+            sender: SENDER,
+            token_ids: vec![U256::from(1u64), U256::from(2u64)],
+            values: vec![U256::from(3u64), U256::from(4u64)],
+            base_layer_data: vec![],
+            exec_layer_data: vec![0xab],
+        };
+
+        assert_eq!(deposit.encode(), payload);
+        assert_eq!(Erc1155BatchDeposit::decode(&payload).unwrap(), deposit);
+    }
+}
@@ -0,0 +1,155 @@
+//! Scaled, rate-limited progress reports for long-running handlers.
+
+use std::time::{Duration, Instant};
+
+use crate::error::CmtError;
+use crate::rollup::Rollup;
+
+/// Turns a work count into [`Rollup::progress`] calls.
+///
+/// A report is only sent when the scaled value changes and at least
+/// `min_interval` has passed since the previous one, so the reporter can be
+/// advanced from tight loops. The final report at full scale is sent by
+/// [`ProgressReporter::finish`], or on drop if the reporter was not
+/// finished.
+pub struct ProgressReporter<'a> {
+    rollup: &'a mut Rollup,
+    total: u64,
+    done: u64,
+    scale: u32,
+    min_interval: Duration,
+    last_value: Option<u32>,
+    last_at: Option<Instant>,
+    finished: bool,
+}
+
+impl<'a> ProgressReporter<'a> {
+    pub const DEFAULT_SCALE: u32 = 100;
+    pub const DEFAULT_MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Tracks `total` units of work, reported as a percentage.
+    pub fn new(rollup: &'a mut Rollup, total: u64) -> Self {
+        Self {
+            rollup,
+            total,
+            done: 0,
+            scale: Self::DEFAULT_SCALE,
+            min_interval: Self::DEFAULT_MIN_INTERVAL,
+            last_value: None,
+            last_at: None,
+            finished: false,
+        }
+    }
+
+    /// Reports values in `0..=scale` instead of percentages.
+    pub fn with_scale(mut self, scale: u32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+
+    /// The rollup, for emitting outputs while the reporter is alive.
+    pub fn rollup(&mut self) -> &mut Rollup {
+        self.rollup
+    }
+
+    /// Marks `n` more units as done.
+    pub fn advance(&mut self, n: u64) -> Result<(), CmtError> {
+        self.set(self.done.saturating_add(n))
+    }
+
+    /// Marks `done` units as done in total.
+    pub fn set(&mut self, done: u64) -> Result<(), CmtError> {
+        self.done = done.min(self.total);
+        let value = self.value();
+        if self.last_value == Some(value) {
+            return Ok(());
+        }
+        if let Some(last_at) = self.last_at
+            && last_at.elapsed() < self.min_interval
+        {
+            return Ok(());
+        }
+        self.report(value)
+    }
+
+    /// Sends the final report at full scale.
+    pub fn finish(mut self) -> Result<(), CmtError> {
+        self.finished = true;
+        self.report_final()
+    }
+
+    /// The current value in `0..=scale`.
+    pub fn value(&self) -> u32 {
+        if self.total == 0 {
+            return self.scale;
+        }
+        (self.done as u128 * self.scale as u128 / self.total as u128) as u32
+    }
+
+    fn report_final(&mut self) -> Result<(), CmtError> {
+        if self.last_value == Some(self.scale) {
+            return Ok(());
+        }
+        self.report(self.scale)
+    }
+
+    fn report(&mut self, value: u32) -> Result<(), CmtError> {
+        self.rollup.progress(value)?;
+        self.last_value = Some(value);
+        self.last_at = Some(Instant::now());
+        Ok(())
+    }
+}
+
+impl Drop for ProgressReporter<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.report_final();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn advances_within_min_interval_send_nothing() {
+        let mut rollup = Rollup::new().unwrap();
+        let mut reporter = ProgressReporter::new(&mut rollup, 10).with_min_interval(HOUR);
+        for _ in 0..5 {
+            reporter.advance(1).unwrap();
+        }
+        assert_eq!(reporter.value(), 50);
+        assert_eq!(reporter.rollup().progress_sent(), [10]);
+        reporter.finish().unwrap();
+    }
+
+    #[test]
+    fn finish_sends_scale_once() {
+        let mut rollup = Rollup::new().unwrap();
+        let mut reporter = ProgressReporter::new(&mut rollup, 4)
+            .with_scale(1000)
+            .with_min_interval(Duration::ZERO);
+        reporter.advance(1).unwrap();
+        reporter.finish().unwrap();
+        assert_eq!(rollup.progress_sent(), [250, 1000]);
+    }
+
+    #[test]
+    fn drop_sends_scale() {
+        let mut rollup = Rollup::new().unwrap();
+        {
+            let mut reporter = ProgressReporter::new(&mut rollup, 10).with_min_interval(HOUR);
+            reporter.advance(3).unwrap();
+        }
+        assert_eq!(rollup.progress_sent(), [30, 100]);
+    }
+}
@@ -0,0 +1,46 @@
+//! Payload encodings for reports and exceptions.
+//!
+//! Besides raw bytes, reports can carry UTF-8 text or JSON, and exceptions
+//! can use Solidity's `Error(string)` revert encoding so explorers decode the
+//! reason an input failed the same way they decode a reverted call.
+
+use crate::abi::{self, AbiError};
+
+/// Selector of `Error(string)`, `0x08c379a0`, in the byte order used by
+/// [`crate::keccak::funsel`].
+pub const ERROR_FUNSEL: u32 = u32::from_le_bytes([0x08, 0xc3, 0x79, 0xa0]);
+
+/// Encodes `message` as `Error(string)` revert data.
+pub fn encode_error(message: &str) -> Vec<u8> {
+    abi::encode_call(ERROR_FUNSEL, &(message,))
+}
+
+/// Decodes `Error(string)` revert data, returning the message.
+pub fn decode_error(payload: &[u8]) -> Result<&str, AbiError> {
+    let (message,): (&str,) = abi::decode_call(payload, ERROR_FUNSEL)?;
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_vector() {
+        let payload = hex::decode(concat!(
+            "08c379a0",
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "7800000000000000000000000000000000000000000000000000000000000000",
+        ))
+        .unwrap();
+        assert_eq!(encode_error("x"), payload);
+        assert_eq!(decode_error(&payload).unwrap(), "x");
+    }
+
+    #[test]
+    fn error_round_trip() {
+        let message = "input rejected: balance too low for a transfer of 100 tokens";
+        assert_eq!(decode_error(&encode_error(message)).unwrap(), message);
+    }
+}
@@ -1,10 +1,27 @@
-use std::{ffi::OsStr, io, mem::MaybeUninit, slice, ptr};
+use std::{ffi::OsStr, mem::MaybeUninit, slice, ptr};
 use hex;
+use serde::Serialize;
+use crate::error::{CmtError, check, check_buffer};
 use crate::generated::*;
-use crate::{path_to_cstring, to_io_result};
+#[cfg(not(target_arch = "riscv64"))]
+use crate::gio::mock::GioHandler;
+use crate::gio::{GioDomain, GioResponse};
+use crate::abi::{AbiDecoder, AbiEncoder, AbiError, Address, U256};
+use crate::notices::{EventNotice, NoticeChunk};
+use crate::outputs::{self, EncodedOutput, JournalEntry, OutputJournal};
+use crate::reports;
+use crate::{buffer_len, path_to_cstring};
 
 pub struct Rollup {
     inner: cmt_rollup_t,
+    next_chunk_id: u64,
+    journal: Option<OutputJournal>,
+    input_index: Option<u64>,
+    #[cfg(not(target_arch = "riscv64"))]
+    gio_handler: Option<Box<dyn GioHandler>>,
+    /// Every value passed to [`Rollup::progress`], for tests.
+    #[cfg(test)]
+    progress_sent: Vec<u32>,
 }
 
 #[derive(Debug, Clone)]
@@ -24,11 +41,103 @@ pub struct Advance {
     pub payload: String,
 }
 
+/// `EvmAdvance(uint256,address,address,uint256,uint256,uint256,uint256,bytes)`,
+/// `0x415bf363`: the call an advance input is encoded as.
+pub const EVM_ADVANCE_FUNSEL: u32 = u32::from_le_bytes([0x41, 0x5b, 0xf3, 0x63]);
+
+impl Advance {
+    /// Encodes the advance as the raw input read by
+    /// [`Rollup::read_advance_state`].
+    pub fn encode_input(&self) -> Result<Vec<u8>, CmtError> {
+        const OP: &str = "Advance::encode_input";
+        let app_contract = Address::from(parse_address_20(&self.app_contract, OP)?);
+        let msg_sender = Address::from(parse_address_20(&self.msg_sender, OP)?);
+        let prev_randao = U256::from(parse_u256_32(Some(self.prev_randao.as_str()), OP)?);
+        let payload = parse_hex_bytes(&self.payload, OP)?;
+
+        let mut enc = AbiEncoder::with_funsel(EVM_ADVANCE_FUNSEL);
+        enc.put_uint(self.metadata.chain_id)
+            .put_address(&app_contract)
+            .put_address(&msg_sender)
+            .put_uint(self.metadata.block_number)
+            .put_uint(self.metadata.block_timestamp)
+            .put_uint256(&prev_randao)
+            .put_uint(self.metadata.index)
+            .put_bytes(&payload);
+        Ok(enc.finish())
+    }
+
+    /// Decodes a raw advance input.
+    pub fn decode_input(input: &[u8]) -> Result<Self, CmtError> {
+        Self::decode_evm_advance(input)
+            .map_err(|e| CmtError::invalid_input("Advance::decode_input", e))
+    }
+
+    fn decode_evm_advance(input: &[u8]) -> Result<Self, AbiError> {
+        let mut dec = AbiDecoder::with_funsel(input, EVM_ADVANCE_FUNSEL)?;
+        let chain_id = dec.get_uint()?;
+        let app_contract = dec.get_address()?;
+        let msg_sender = dec.get_address()?;
+        let block_number = dec.get_uint()?;
+        let block_timestamp = dec.get_uint()?;
+        let prev_randao = dec.get_uint256()?;
+        let index = dec.get_uint()?;
+        let payload = dec.get_bytes()?;
+        Ok(Self {
+            metadata: Metadata {
+                chain_id,
+                block_number,
+                block_timestamp,
+                index,
+            },
+            app_contract: to_hex(&app_contract.0),
+            msg_sender: to_hex(&msg_sender.0),
+            prev_randao: to_hex(&prev_randao.0),
+            payload: to_hex(payload),
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Inspect {
     pub payload: String,
 }
 
+/// The kinds of output an application can emit through [`Rollup`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputKind {
+    Voucher,
+    DelegateCallVoucher,
+    Notice,
+    Report,
+    Exception,
+}
+
+impl OutputKind {
+    /// Bytes libcmt writes around the payload: the selector, the static
+    /// arguments and the offset and length words of the `bytes` argument.
+    /// Reports and exceptions are written as is.
+    fn overhead(self) -> usize {
+        match self {
+            Self::Voucher => 4 + 4 * 32,
+            Self::DelegateCallVoucher => 4 + 3 * 32,
+            Self::Notice => 4 + 2 * 32,
+            Self::Report | Self::Exception => 0,
+        }
+    }
+
+    /// Bytes an output of `kind` with a `payload_len` byte payload takes in
+    /// the TX buffer.
+    fn encoded_len(self, payload_len: usize) -> usize {
+        match self {
+            Self::Voucher | Self::DelegateCallVoucher | Self::Notice => {
+                self.overhead() + payload_len.div_ceil(32) * 32
+            }
+            Self::Report | Self::Exception => payload_len,
+        }
+    }
+}
+
 fn to_hex(bytes: &[u8]) -> String {
     let mut s = String::with_capacity(bytes.len() * 2 + 2);
     s.push_str("0x");
@@ -80,28 +189,28 @@ fn convert_advance(c_adv: &cmt_rollup_advance_t) -> Advance {
     }
 }
 
-fn parse_address_20(s: &str) -> io::Result<cmt_abi_address_t> {
+fn parse_address_20(s: &str, op: &'static str) -> Result<cmt_abi_address_t, CmtError> {
     let s2 = s.strip_prefix("0x").unwrap_or(s);
     if s2.len() != 40 {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
+        return Err(CmtError::invalid_input(
+            op,
             format!("address must be 40 hex chars (20 bytes), got {}", s2.len()),
         ));
     }
-    let bytes = parse_hex_bytes(s)?;
+    let bytes = parse_hex_bytes(s, op)?;
     let mut data = [0u8; 20];
     data.copy_from_slice(&bytes);
     Ok(cmt_abi_address_t { data })
 }
 
-fn parse_u256_32(s: Option<&str>) -> io::Result<cmt_abi_u256_t> {
+fn parse_u256_32(s: Option<&str>, op: &'static str) -> Result<cmt_abi_u256_t, CmtError> {
     let mut data = [0u8; 32];
 
     if let Some(s) = s {
-        let raw = parse_hex_bytes(s)?;
+        let raw = parse_hex_bytes(s, op)?;
         if raw.len() > 32 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
+            return Err(CmtError::invalid_input(
+                op,
                 "u256 hex too long (>32 bytes)",
             ));
         }
@@ -111,7 +220,7 @@ fn parse_u256_32(s: Option<&str>) -> io::Result<cmt_abi_u256_t> {
     Ok(cmt_abi_u256_t { data })
 }
 
-fn parse_hex_bytes(s: &str) -> io::Result<Vec<u8>> {
+fn parse_hex_bytes(s: &str, op: &'static str) -> Result<Vec<u8>, CmtError> {
     let mut s = s.strip_prefix("0x").unwrap_or(s).to_string();
 
     if s.is_empty() {
@@ -123,17 +232,26 @@ fn parse_hex_bytes(s: &str) -> io::Result<Vec<u8>> {
     }
 
     hex::decode(&s).map_err(|e| {
-        io::Error::new(io::ErrorKind::InvalidInput, format!("invalid hex: {e}"))
+        CmtError::invalid_input(op, format!("invalid hex: {e}"))
     })
 }
 
 impl Rollup {
-    pub fn new() -> io::Result<Self> {
+    pub fn new() -> Result<Self, CmtError> {
         let mut state = std::mem::MaybeUninit::<cmt_rollup_t>::zeroed();
         let rc = unsafe { cmt_rollup_init(state.as_mut_ptr()) };
-        to_io_result(rc)?;
+        check(rc, "cmt_rollup_init")?;
         let inner = unsafe { state.assume_init() };
-        Ok(Self { inner })
+        Ok(Self {
+            inner,
+            next_chunk_id: 0,
+            journal: None,
+            input_index: None,
+            #[cfg(not(target_arch = "riscv64"))]
+            gio_handler: None,
+            #[cfg(test)]
+            progress_sent: Vec::new(),
+        })
     }
 
     pub fn emit_voucher(
@@ -141,12 +259,14 @@ impl Rollup {
         address_hex: &str, 
         value_hex: Option<&str>,
         payload_hex: &str, 
-    ) -> io::Result<u64> {
+    ) -> Result<u64, CmtError> {
+        const OP: &str = "cmt_rollup_emit_voucher";
         let mut index: u64 = 0;
 
-        let address = parse_address_20(address_hex)?;
-        let value = parse_u256_32(value_hex)?;
-        let payload_bytes = parse_hex_bytes(payload_hex)?;
+        let address = parse_address_20(address_hex, OP)?;
+        let value = parse_u256_32(value_hex, OP)?;
+        let payload_bytes = parse_hex_bytes(payload_hex, OP)?;
+        self.check_payload_len(OutputKind::Voucher, OP, payload_bytes.len())?;
 
         let c_payload = cmt_abi_bytes_t {
             data: if payload_bytes.is_empty() {
@@ -157,7 +277,7 @@ impl Rollup {
             length: payload_bytes.len() as usize,
         };
     
-        to_io_result(unsafe {
+        let rc = unsafe {
             cmt_rollup_emit_voucher(
                 &mut self.inner,
                 &address as *const cmt_abi_address_t,
@@ -165,7 +285,12 @@ impl Rollup {
                 &c_payload as *const cmt_abi_bytes_t,
                 &mut index as *mut u64,
             )
-        })?;
+        };
+        let requested = OutputKind::Voucher.encoded_len(payload_bytes.len());
+        check_buffer(rc, OP, requested, self.tx_len())?;
+        self.record_output(OutputKind::Voucher, index, || {
+            outputs::encode_voucher_output(&Address::from(address), &U256::from(value), &payload_bytes)
+        });
     
         Ok(index)
     }
@@ -174,10 +299,24 @@ impl Rollup {
         &mut self,
         address_hex: &str, 
         payload_hex: &str,
-    ) -> io::Result<u64> {
+    ) -> Result<u64, CmtError> {
+        const OP: &str = "cmt_rollup_emit_delegate_call_voucher";
+        let address = parse_address_20(address_hex, OP)?;
+        let payload_bytes = parse_hex_bytes(payload_hex, OP)?;
+        self.emit_delegate_call_voucher_bytes(&Address::from(address), &payload_bytes)
+    }
+
+    /// Emits a delegate call voucher without checking `destination`; see
+    /// [`crate::vouchers::DelegateCallVoucherBuilder`] for a checked builder.
+    pub fn emit_delegate_call_voucher_bytes(
+        &mut self,
+        destination: &Address,
+        payload_bytes: &[u8],
+    ) -> Result<u64, CmtError> {
+        const OP: &str = "cmt_rollup_emit_delegate_call_voucher";
         let mut index = 0u64;
-        let address = parse_address_20(address_hex)?;
-        let payload_bytes = parse_hex_bytes(payload_hex)?;
+        let address = cmt_abi_address_t::from(*destination);
+        self.check_payload_len(OutputKind::DelegateCallVoucher, OP, payload_bytes.len())?;
 
         let c_payload = cmt_abi_bytes_t {
             data: if payload_bytes.is_empty() {
@@ -187,15 +326,23 @@ impl Rollup {
             },
             length: payload_bytes.len() as usize,
         };
-        to_io_result(unsafe {
+        let rc = unsafe {
             cmt_rollup_emit_delegate_call_voucher(&mut self.inner, &address as *const cmt_abi_address_t, &c_payload as *const cmt_abi_bytes_t, &mut index as *mut u64)
-        })?;
+        };
+        let requested = OutputKind::DelegateCallVoucher.encoded_len(payload_bytes.len());
+        check_buffer(rc, OP, requested, self.tx_len())?;
+        self.record_output(OutputKind::DelegateCallVoucher, index, || {
+            outputs::encode_delegate_call_voucher_output(destination, payload_bytes)
+        });
         Ok(index)
     }
 
-    pub fn emit_notice(&mut self, payload_hex: &str) -> io::Result<u64> {
-        let payload_bytes = parse_hex_bytes(payload_hex)?;
+    pub fn emit_notice(&mut self, payload_hex: &str) -> Result<u64, CmtError> {
+        self.emit_notice_bytes(&parse_hex_bytes(payload_hex, "cmt_rollup_emit_notice")?)
+    }
 
+    pub fn emit_notice_bytes(&mut self, payload_bytes: &[u8]) -> Result<u64, CmtError> {
+        self.check_payload_len(OutputKind::Notice, "cmt_rollup_emit_notice", payload_bytes.len())?;
         let c_payload = cmt_abi_bytes_t {
             data: if payload_bytes.is_empty() {
                 ptr::null_mut()
@@ -206,13 +353,47 @@ impl Rollup {
         };
 
         let mut index = 0u64;
-        to_io_result(unsafe { cmt_rollup_emit_notice(&mut self.inner, &c_payload as *const cmt_abi_bytes_t, &mut index as *mut u64) })?;
+        let rc = unsafe { cmt_rollup_emit_notice(&mut self.inner, &c_payload as *const cmt_abi_bytes_t, &mut index as *mut u64) };
+        let requested = OutputKind::Notice.encoded_len(payload_bytes.len());
+        check_buffer(rc, "cmt_rollup_emit_notice", requested, self.tx_len())?;
+        self.record_output(OutputKind::Notice, index, || outputs::encode_notice_output(payload_bytes));
         Ok(index)
     }
 
-    pub fn emit_report(&mut self, payload_hex: &str) -> io::Result<()> {
-        let payload_bytes = parse_hex_bytes(payload_hex)?;
+    /// Emits an event log style notice; see [`crate::notices`].
+    pub fn emit_event_notice(&mut self, notice: &EventNotice) -> Result<u64, CmtError> {
+        self.emit_notice_bytes(&notice.encode())
+    }
+
+    /// Emits `payload` as one or more [`NoticeChunk`] notices sized to fit
+    /// the TX buffer, returning the notice indices in order. Use a
+    /// [`crate::notices::NoticeReassembler`] to put the payload back together.
+    pub fn emit_notice_chunked(&mut self, payload: &[u8]) -> Result<Vec<u64>, CmtError> {
+        let max = self.max_output_payload_len(OutputKind::Notice);
+        let chunk_len = max.saturating_sub(NoticeChunk::OVERHEAD);
+        if chunk_len == 0 {
+            return Err(CmtError::PayloadTooLarge {
+                op: "emit_notice_chunked",
+                max: 0,
+                got: payload.len(),
+            });
+        }
+
+        let id = self.next_chunk_id;
+        self.next_chunk_id += 1;
+        NoticeChunk::split(id, payload, chunk_len)
+            .map_err(|e| CmtError::invalid_input("emit_notice_chunked", e))?
+            .iter()
+            .map(|chunk| self.emit_notice_bytes(&chunk.encode()))
+            .collect()
+    }
+
+    pub fn emit_report(&mut self, payload_hex: &str) -> Result<(), CmtError> {
+        self.emit_report_bytes(&parse_hex_bytes(payload_hex, "cmt_rollup_emit_report")?)
+    }
 
+    pub fn emit_report_bytes(&mut self, payload_bytes: &[u8]) -> Result<(), CmtError> {
+        self.check_payload_len(OutputKind::Report, "cmt_rollup_emit_report", payload_bytes.len())?;
         let c_payload = cmt_abi_bytes_t {
             data: if payload_bytes.is_empty() {
                 ptr::null_mut()
@@ -222,12 +403,38 @@ impl Rollup {
             length: payload_bytes.len() as usize,
         };
 
-        to_io_result(unsafe { cmt_rollup_emit_report(&mut self.inner, &c_payload as *const cmt_abi_bytes_t) })
+        let rc = unsafe { cmt_rollup_emit_report(&mut self.inner, &c_payload as *const cmt_abi_bytes_t) };
+        let requested = OutputKind::Report.encoded_len(payload_bytes.len());
+        check_buffer(rc, "cmt_rollup_emit_report", requested, self.tx_len())
+    }
+
+    /// Emits a report whose payload is the UTF-8 text `text`.
+    pub fn emit_report_text(&mut self, text: &str) -> Result<(), CmtError> {
+        self.emit_report_bytes(text.as_bytes())
+    }
+
+    /// Emits a report whose payload is `value` serialized as JSON.
+    pub fn emit_report_json<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CmtError> {
+        let payload = serde_json::to_vec(value)
+            .map_err(|e| CmtError::invalid_input("emit_report_json", e))?;
+        self.emit_report_bytes(&payload)
+    }
+
+    /// Emits a report whose payload is `message` as `Error(string)` revert data.
+    pub fn emit_report_error(&mut self, message: &str) -> Result<(), CmtError> {
+        self.emit_report_bytes(&reports::encode_error(message))
     }
 
-    pub fn emit_exception(&mut self, payload_hex: &str) -> io::Result<()> {
-        let payload_bytes = parse_hex_bytes(payload_hex)?;
+    pub fn emit_exception(&mut self, payload_hex: &str) -> Result<(), CmtError> {
+        self.emit_exception_bytes(&parse_hex_bytes(payload_hex, "cmt_rollup_emit_exception")?)
+    }
 
+    pub fn emit_exception_bytes(&mut self, payload_bytes: &[u8]) -> Result<(), CmtError> {
+        self.check_payload_len(
+            OutputKind::Exception,
+            "cmt_rollup_emit_exception",
+            payload_bytes.len(),
+        )?;
         let c_payload = cmt_abi_bytes_t {
             data: if payload_bytes.is_empty() {
                 ptr::null_mut()
@@ -236,28 +443,48 @@ impl Rollup {
             },
             length: payload_bytes.len() as usize,
         };
-        to_io_result(unsafe { cmt_rollup_emit_exception(&mut self.inner, &c_payload as *const cmt_abi_bytes_t) })
+        let rc = unsafe { cmt_rollup_emit_exception(&mut self.inner, &c_payload as *const cmt_abi_bytes_t) };
+        let requested = OutputKind::Exception.encoded_len(payload_bytes.len());
+        check_buffer(rc, "cmt_rollup_emit_exception", requested, self.tx_len())
+    }
+
+    /// Emits an exception whose payload is `message` as `Error(string)`
+    /// revert data, selector `0x08c379a0`.
+    pub fn emit_exception_error(&mut self, message: &str) -> Result<(), CmtError> {
+        self.emit_exception_bytes(&reports::encode_error(message))
+    }
+
+    pub fn progress(&mut self, value: u32) -> Result<(), CmtError> {
+        check(unsafe { cmt_rollup_progress(&mut self.inner, value) }, "cmt_rollup_progress")?;
+        #[cfg(test)]
+        self.progress_sent.push(value);
+        Ok(())
     }
 
-    pub fn progress(&mut self, value: u32) -> io::Result<()> {
-        to_io_result(unsafe { cmt_rollup_progress(&mut self.inner, value) })
+    #[cfg(test)]
+    pub(crate) fn progress_sent(&self) -> &[u32] {
+        &self.progress_sent
     }
 
-    pub fn read_advance_state(&mut self) -> io::Result<Advance> {
+    pub fn read_advance_state(&mut self) -> Result<Advance, CmtError> {
         let mut c_adv = MaybeUninit::<cmt_rollup_advance_t>::uninit();
-        to_io_result(unsafe {
-            cmt_rollup_read_advance_state(&mut self.inner, c_adv.as_mut_ptr())
-        })?;
+        check(
+            unsafe { cmt_rollup_read_advance_state(&mut self.inner, c_adv.as_mut_ptr()) },
+            "cmt_rollup_read_advance_state",
+        )?;
         let c_adv = unsafe { c_adv.assume_init() };
+        self.input_index = Some(c_adv.index as u64);
         Ok(convert_advance(&c_adv))
     }
 
-    pub fn read_inspect_state(&mut self) -> io::Result<Inspect> {
+    pub fn read_inspect_state(&mut self) -> Result<Inspect, CmtError> {
         let mut c_inspect = MaybeUninit::<cmt_rollup_inspect_t>::uninit();
-        to_io_result(unsafe {
-            cmt_rollup_read_inspect_state(&mut self.inner, c_inspect.as_mut_ptr())
-        })?;
+        check(
+            unsafe { cmt_rollup_read_inspect_state(&mut self.inner, c_inspect.as_mut_ptr()) },
+            "cmt_rollup_read_inspect_state",
+        )?;
         let c_inspect = unsafe { c_inspect.assume_init() };
+        self.input_index = None;
         let payload = unsafe {
             let len = c_inspect.payload.length as usize;
             let ptr = c_inspect.payload.data as *const u8;
@@ -272,27 +499,160 @@ impl Rollup {
         Ok(Inspect { payload: to_hex(&payload) })
     }
 
-    pub fn finish(&mut self, finish: &mut cmt_rollup_finish_t) -> io::Result<()> {
-        to_io_result(unsafe { cmt_rollup_finish(&mut self.inner, finish) })
+    pub fn finish(&mut self, finish: &mut cmt_rollup_finish_t) -> Result<(), CmtError> {
+        check(unsafe { cmt_rollup_finish(&mut self.inner, finish) }, "cmt_rollup_finish")
     }
 
-    pub fn gio_request(&mut self, request: &mut cmt_gio_t) -> io::Result<()> {
-        to_io_result(unsafe { cmt_gio_request(&mut self.inner, request) })
+    pub fn gio_request(&mut self, request: &mut cmt_gio_t) -> Result<(), CmtError> {
+        let rc = unsafe { cmt_gio_request(&mut self.inner, request) };
+        check_buffer(rc, "cmt_gio_request", request.id_length as usize, self.tx_len())
     }
 
-    pub fn load_merkle<P: AsRef<OsStr>>(&mut self, path: P) -> io::Result<()> {
-        let path = path_to_cstring(path.as_ref())?;
-        to_io_result(unsafe { cmt_rollup_load_merkle(&mut self.inner, path.as_ptr()) })
+    /// Sends a GIO request for `id` in `domain` and returns the host's
+    /// response. A non-zero response code is returned as
+    /// [`CmtError::Rejected`].
+    pub fn gio(&mut self, domain: GioDomain, id: &[u8]) -> Result<GioResponse, CmtError> {
+        const OP: &str = "cmt_gio_request";
+        let available = self.tx_len();
+        if id.len() > available {
+            return Err(CmtError::BufferTooSmall {
+                op: OP,
+                requested: id.len(),
+                available,
+            });
+        }
+
+        #[cfg(not(target_arch = "riscv64"))]
+        if let Some(handler) = self.gio_handler.as_mut()
+            && let Some(response) = handler.handle(domain, id)
+        {
+            if response.code != 0 {
+                return Err(CmtError::Rejected {
+                    op: OP,
+                    code: response.code,
+                });
+            }
+            return Ok(response);
+        }
+
+        let mut request = cmt_gio_t {
+            domain: domain.raw(),
+            id: id.as_ptr() as *mut ::std::os::raw::c_void,
+            id_length: id.len() as u32,
+            response_code: 0,
+            response_data: ptr::null_mut(),
+            response_data_length: 0,
+        };
+        self.gio_request(&mut request)?;
+        if request.response_code != 0 {
+            return Err(CmtError::Rejected {
+                op: OP,
+                code: request.response_code,
+            });
+        }
+
+        let len = request.response_data_length as usize;
+        let ptr = request.response_data as *const u8;
+        let data = if len > 0 && !ptr.is_null() {
+            unsafe { slice::from_raw_parts(ptr, len) }.to_vec()
+        } else {
+            Vec::new()
+        };
+        Ok(GioResponse {
+            code: request.response_code,
+            data,
+        })
+    }
+
+    /// Answers [`Rollup::gio`] requests from `handler` before falling back
+    /// to libcmt. Only available off the machine, for testing.
+    ///
+    /// Raw [`Rollup::gio_request`] calls bypass the handler and always go to
+    /// libcmt, since the handler has nowhere to keep the response data a
+    /// `cmt_gio_t` points to.
+    #[cfg(not(target_arch = "riscv64"))]
+    pub fn set_gio_handler<H: GioHandler + 'static>(&mut self, handler: H) {
+        self.gio_handler = Some(Box::new(handler));
     }
 
-    pub fn save_merkle<P: AsRef<OsStr>>(&mut self, path: P) -> io::Result<()> {
-        let path = path_to_cstring(path.as_ref())?;
-        to_io_result(unsafe { cmt_rollup_save_merkle(&mut self.inner, path.as_ptr()) })
+    pub fn load_merkle<P: AsRef<OsStr>>(&mut self, path: P) -> Result<(), CmtError> {
+        const OP: &str = "cmt_rollup_load_merkle";
+        let path = path_to_cstring(path.as_ref()).map_err(|e| CmtError::invalid_input(OP, e))?;
+        check(unsafe { cmt_rollup_load_merkle(&mut self.inner, path.as_ptr()) }, OP)
+    }
+
+    pub fn save_merkle<P: AsRef<OsStr>>(&mut self, path: P) -> Result<(), CmtError> {
+        const OP: &str = "cmt_rollup_save_merkle";
+        let path = path_to_cstring(path.as_ref()).map_err(|e| CmtError::invalid_input(OP, e))?;
+        check(unsafe { cmt_rollup_save_merkle(&mut self.inner, path.as_ptr()) }, OP)
     }
 
     pub fn reset_merkle(&mut self) {
         unsafe { cmt_rollup_reset_merkle(&mut self.inner) }
     }
+
+    /// Starts recording the vouchers, delegate call vouchers and notices
+    /// emitted from now on. The journal is off by default: it keeps one
+    /// entry per output for the life of the rollup, so long-running
+    /// applications should [`OutputJournal::clear`] it, e.g. after each
+    /// input, through [`Rollup::journal_mut`].
+    pub fn enable_journal(&mut self) {
+        self.journal.get_or_insert_with(OutputJournal::new);
+    }
+
+    /// The outputs recorded since [`Rollup::enable_journal`], if enabled.
+    pub fn journal(&self) -> Option<&OutputJournal> {
+        self.journal.as_ref()
+    }
+
+    pub fn journal_mut(&mut self) -> Option<&mut OutputJournal> {
+        self.journal.as_mut()
+    }
+
+    /// Journals an emitted output. `encode` re-encodes it to get its hash,
+    /// and only runs while the journal is enabled.
+    fn record_output(&mut self, kind: OutputKind, index: u64, encode: impl FnOnce() -> EncodedOutput) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.record(JournalEntry {
+                kind,
+                index,
+                hash: encode().hash,
+                input_index: self.input_index,
+            });
+        }
+    }
+
+    /// Largest payload an output of `kind` can carry once encoded into the
+    /// TX buffer.
+    pub fn max_output_payload_len(&mut self, kind: OutputKind) -> usize {
+        let available = self.tx_len().saturating_sub(kind.overhead());
+        match kind {
+            // The payload is padded to a whole number of 32-byte words.
+            OutputKind::Voucher | OutputKind::DelegateCallVoucher | OutputKind::Notice => {
+                available - available % 32
+            }
+            OutputKind::Report | OutputKind::Exception => available,
+        }
+    }
+
+    fn check_payload_len(
+        &mut self,
+        kind: OutputKind,
+        op: &'static str,
+        got: usize,
+    ) -> Result<(), CmtError> {
+        let max = self.max_output_payload_len(kind);
+        if got > max {
+            return Err(CmtError::PayloadTooLarge { op, max, got });
+        }
+        Ok(())
+    }
+
+    /// Size of the TX buffer outputs are encoded into.
+    fn tx_len(&mut self) -> usize {
+        let buf = unsafe { cmt_io_get_tx(self.inner.io.as_mut_ptr()) };
+        buffer_len(&buf)
+    }
 }
 
 impl Drop for Rollup {
@@ -300,3 +660,129 @@ impl Drop for Rollup {
         unsafe { cmt_rollup_fini(&mut self.inner) }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `EvmAdvance(1, 0x1111..11, 0x2222..22, 2, 3, 4, 5, 0xdeadbeef)`.
+    const EVM_ADVANCE: &str = concat!(
+        "415bf363",
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "0000000000000000000000001111111111111111111111111111111111111111",
+        "0000000000000000000000002222222222222222222222222222222222222222",
+        "0000000000000000000000000000000000000000000000000000000000000002",
+        "0000000000000000000000000000000000000000000000000000000000000003",
+        "0000000000000000000000000000000000000000000000000000000000000004",
+        "0000000000000000000000000000000000000000000000000000000000000005",
+        "0000000000000000000000000000000000000000000000000000000000000100",
+        "0000000000000000000000000000000000000000000000000000000000000004",
+        "deadbeef00000000000000000000000000000000000000000000000000000000",
+    );
+
+    #[test]
+    fn advance_input_round_trip() {
+        let input = hex::decode(EVM_ADVANCE).unwrap();
+        let advance = Advance::decode_input(&input).unwrap();
+        assert_eq!(advance.metadata.chain_id, 1);
+        assert_eq!(advance.app_contract, format!("0x{}", "11".repeat(20)));
+        assert_eq!(advance.msg_sender, format!("0x{}", "22".repeat(20)));
+        assert_eq!(advance.metadata.block_number, 2);
+        assert_eq!(advance.metadata.block_timestamp, 3);
+        assert_eq!(advance.prev_randao, format!("0x{:064x}", 4));
+        assert_eq!(advance.metadata.index, 5);
+        assert_eq!(advance.payload, "0xdeadbeef");
+        assert_eq!(advance.encode_input().unwrap(), input);
+    }
+
+    #[test]
+    fn advance_input_rejects_other_calls() {
+        let mut input = hex::decode(EVM_ADVANCE).unwrap();
+        input[0] = 0;
+        assert!(matches!(
+            Advance::decode_input(&input),
+            Err(CmtError::InvalidInput { .. })
+        ));
+    }
+
+    #[test]
+    fn advance_input_read_back_from_mock() {
+        let advance = Advance::decode_input(&hex::decode(EVM_ADVANCE).unwrap()).unwrap();
+        let path = std::env::temp_dir().join(format!("cmt-advance-{}.bin", std::process::id()));
+        std::fs::write(&path, advance.encode_input().unwrap()).unwrap();
+        // The mock reads CMT_INPUTS when the rollup is created. No other
+        // test sets it, and the ones that do not call `finish` ignore it.
+        unsafe { std::env::set_var("CMT_INPUTS", format!("0:{}", path.display())) };
+        let mut rollup = Rollup::new().unwrap();
+        let mut finish = cmt_rollup_finish_t {
+            accept_previous_request: true,
+            next_request_type: 0,
+            next_request_payload_length: 0,
+        };
+        rollup.finish(&mut finish).unwrap();
+        let read = rollup.read_advance_state().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(finish.next_request_type, HTIF_YIELD_REASON_ADVANCE as _);
+        assert_eq!(read.metadata.chain_id, advance.metadata.chain_id);
+        assert_eq!(read.metadata.block_number, advance.metadata.block_number);
+        assert_eq!(
+            read.metadata.block_timestamp,
+            advance.metadata.block_timestamp
+        );
+        assert_eq!(read.metadata.index, advance.metadata.index);
+        assert_eq!(read.app_contract, advance.app_contract);
+        assert_eq!(read.msg_sender, advance.msg_sender);
+        assert_eq!(read.prev_randao, advance.prev_randao);
+        assert_eq!(read.payload, advance.payload);
+    }
+
+    /// The first `len` bytes of the TX buffer, where libcmt encodes the
+    /// output it last emitted.
+    fn tx_bytes(rollup: &mut Rollup, len: usize) -> Vec<u8> {
+        let buf = unsafe { cmt_io_get_tx(rollup.inner.io.as_mut_ptr()) };
+        assert!(len <= buffer_len(&buf));
+        unsafe { slice::from_raw_parts(buf.begin as *const u8, len) }.to_vec()
+    }
+
+    #[test]
+    fn voucher_output_matches_tx_buffer() {
+        let destination = Address::new([0x11; 20]);
+        let value = U256::from(42u64);
+        let payload = b"voucher payload longer than one ABI word".to_vec();
+        let mut rollup = Rollup::new().unwrap();
+        rollup
+            .emit_voucher(
+                &to_hex(&destination.0),
+                Some(&to_hex(&value.0)),
+                &to_hex(&payload),
+            )
+            .unwrap();
+
+        let expected = outputs::encode_voucher_output(&destination, &value, &payload);
+        assert_eq!(tx_bytes(&mut rollup, expected.bytes.len()), expected.bytes);
+    }
+
+    #[test]
+    fn delegate_call_voucher_output_matches_tx_buffer() {
+        let destination = Address::new([0x22; 20]);
+        let payload = vec![0xab; 5];
+        let mut rollup = Rollup::new().unwrap();
+        rollup
+            .emit_delegate_call_voucher_bytes(&destination, &payload)
+            .unwrap();
+
+        let expected = outputs::encode_delegate_call_voucher_output(&destination, &payload);
+        assert_eq!(tx_bytes(&mut rollup, expected.bytes.len()), expected.bytes);
+    }
+
+    #[test]
+    fn notice_output_matches_tx_buffer() {
+        let payload = b"notice".to_vec();
+        let mut rollup = Rollup::new().unwrap();
+        rollup.emit_notice_bytes(&payload).unwrap();
+
+        let expected = outputs::encode_notice_output(&payload);
+        assert_eq!(tx_bytes(&mut rollup, expected.bytes.len()), expected.bytes);
+    }
+}
@@ -0,0 +1,229 @@
+//! Checked construction of delegate call vouchers.
+//!
+//! A delegate call voucher runs code of the destination contract in the
+//! context of the application contract, so a wrong destination can take
+//! over the application's assets. [`DelegateCallVoucherBuilder`] only
+//! builds vouchers for destinations on an explicit allow-list.
+
+use std::collections::BTreeSet;
+
+use crate::abi::{self, AbiEncode, AbiError, Address};
+use crate::error::CmtError;
+use crate::keccak;
+use crate::rollup::Rollup;
+
+/// A call made by a delegate call voucher: a function selector followed by
+/// the ABI encoded arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelegateCall {
+    pub funsel: u32,
+    pub args: Vec<u8>,
+    /// The function signature, when known, for logging.
+    pub signature: Option<String>,
+}
+
+impl DelegateCall {
+    /// A call to `signature`, e.g. `transfer(address,uint256)`, with `args`
+    /// encoded as its parameter tuple.
+    pub fn new<T: AbiEncode + ?Sized>(signature: &str, args: &T) -> Self {
+        Self {
+            funsel: keccak::funsel(signature),
+            args: abi::encode_params(args),
+            signature: Some(signature.to_string()),
+        }
+    }
+
+    /// A call with an already encoded argument tuple.
+    pub fn from_funsel(funsel: u32, args: Vec<u8>) -> Self {
+        Self {
+            funsel,
+            args,
+            signature: None,
+        }
+    }
+
+    /// Splits calldata into its selector and arguments.
+    pub fn decode(calldata: &[u8]) -> Result<Self, AbiError> {
+        let funsel = abi::AbiDecoder::peek_funsel(calldata).ok_or(AbiError::Truncated {
+            offset: 0,
+            needed: 4,
+            available: calldata.len(),
+        })?;
+        Ok(Self::from_funsel(funsel, calldata[4..].to_vec()))
+    }
+
+    /// The calldata: selector followed by the arguments.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut calldata = Vec::with_capacity(4 + self.args.len());
+        calldata.extend_from_slice(&self.funsel.to_le_bytes());
+        calldata.extend_from_slice(&self.args);
+        calldata
+    }
+
+    /// The selector as `0x`-prefixed hex, in wire order.
+    pub fn selector_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.funsel.to_le_bytes()))
+    }
+}
+
+/// A delegate call voucher whose destination passed the allow-list check.
+/// Only [`DelegateCallVoucherBuilder::build`] creates one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelegateCallVoucher {
+    destination: Address,
+    call: DelegateCall,
+}
+
+impl DelegateCallVoucher {
+    pub fn destination(&self) -> &Address {
+        &self.destination
+    }
+
+    pub fn call(&self) -> &DelegateCall {
+        &self.call
+    }
+
+    pub fn payload(&self) -> Vec<u8> {
+        self.call.encode()
+    }
+
+    /// Emits the voucher and returns its output index, logging the call to
+    /// stderr when libcmt debug output is enabled.
+    pub fn emit(&self, rollup: &mut Rollup) -> Result<u64, CmtError> {
+        if crate::util::debug_enabled() {
+            eprintln!("delegate call voucher: {}", self.describe());
+        }
+        rollup.emit_delegate_call_voucher_bytes(&self.destination, &self.payload())
+    }
+
+    /// A one line description of the call, with one argument word per item.
+    pub fn describe(&self) -> String {
+        let words: Vec<String> = self
+            .call
+            .args
+            .chunks(32)
+            .map(|word| format!("0x{}", hex::encode(word)))
+            .collect();
+        let name = match &self.call.signature {
+            Some(signature) => format!("{signature} [{}]", self.call.selector_hex()),
+            None => self.call.selector_hex(),
+        };
+        format!("{} -> {name}({})", self.destination, words.join(", "))
+    }
+}
+
+/// Builds [`DelegateCallVoucher`]s, refusing destinations that are not on
+/// the allow-list. Keep a configured builder around and clone it for each
+/// voucher.
+#[derive(Debug, Clone, Default)]
+pub struct DelegateCallVoucherBuilder {
+    allow_list: BTreeSet<Address>,
+    destination: Option<Address>,
+    call: Option<DelegateCall>,
+}
+
+impl DelegateCallVoucherBuilder {
+    pub fn new<I: IntoIterator<Item = Address>>(allow_list: I) -> Self {
+        Self {
+            allow_list: allow_list.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Adds `address` to the allow-list.
+    pub fn allow(mut self, address: Address) -> Self {
+        self.allow_list.insert(address);
+        self
+    }
+
+    pub fn is_allowed(&self, address: &Address) -> bool {
+        self.allow_list.contains(address)
+    }
+
+    pub fn destination(mut self, destination: Address) -> Self {
+        self.destination = Some(destination);
+        self
+    }
+
+    pub fn call(mut self, call: DelegateCall) -> Self {
+        self.call = Some(call);
+        self
+    }
+
+    pub fn build(self) -> Result<DelegateCallVoucher, CmtError> {
+        const OP: &str = "DelegateCallVoucherBuilder::build";
+        let destination = self
+            .destination
+            .ok_or_else(|| CmtError::invalid_input(OP, "no destination set"))?;
+        let call = self
+            .call
+            .ok_or_else(|| CmtError::invalid_input(OP, "no call set"))?;
+        if !self.allow_list.contains(&destination) {
+            return Err(CmtError::invalid_input(
+                OP,
+                format!("destination {destination} is not on the allow-list"),
+            ));
+        }
+        Ok(DelegateCallVoucher { destination, call })
+    }
+
+    /// Builds the voucher and emits it.
+    pub fn emit(self, rollup: &mut Rollup) -> Result<u64, CmtError> {
+        self.build()?.emit(rollup)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::outputs;
+    use crate::rollup::OutputKind;
+
+    const ALLOWED: Address = Address::new([0x11; 20]);
+
+    fn transfer() -> DelegateCall {
+        DelegateCall::new(
+            "transfer(address,uint256)",
+            &(Address::new([0x22; 20]), 100u64),
+        )
+    }
+
+    fn is_invalid_input(result: Result<DelegateCallVoucher, CmtError>) -> bool {
+        matches!(result, Err(CmtError::InvalidInput { .. }))
+    }
+
+    #[test]
+    fn destination_off_the_list_is_refused() {
+        let builder = DelegateCallVoucherBuilder::new([ALLOWED])
+            .destination(Address::new([0x33; 20]))
+            .call(transfer());
+        assert!(is_invalid_input(builder.build()));
+    }
+
+    #[test]
+    fn missing_destination_or_call_is_an_error() {
+        let builder = DelegateCallVoucherBuilder::new([ALLOWED]);
+        assert!(is_invalid_input(builder.clone().call(transfer()).build()));
+        assert!(is_invalid_input(builder.destination(ALLOWED).build()));
+    }
+
+    #[test]
+    fn allowed_voucher_emits_the_encoded_call() {
+        let voucher = DelegateCallVoucherBuilder::new([ALLOWED])
+            .destination(ALLOWED)
+            .call(transfer())
+            .build()
+            .unwrap();
+        assert_eq!(voucher.destination(), &ALLOWED);
+        assert_eq!(voucher.payload(), transfer().encode());
+
+        let mut rollup = Rollup::new().unwrap();
+        rollup.enable_journal();
+        let index = voucher.emit(&mut rollup).unwrap();
+
+        let entry = *rollup.journal().unwrap().find(index).unwrap();
+        let expected = outputs::encode_delegate_call_voucher_output(&ALLOWED, &transfer().encode());
+        assert_eq!(entry.kind, OutputKind::DelegateCallVoucher);
+        assert_eq!(entry.hash, expected.hash);
+    }
+}